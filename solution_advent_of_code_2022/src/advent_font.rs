@@ -0,0 +1,146 @@
+use std::fmt::Display;
+
+pub const GLYPH_WIDTH: usize = 4;
+pub const GLYPH_HEIGHT: usize = 6;
+/// Columns between two glyphs on a screen.
+pub const GLYPH_SPACING: usize = 1;
+
+type Glyph = [&'static str; GLYPH_HEIGHT];
+
+// Capital letters as they show up in advent of code puzzles,
+// '#' as lit and '.' as dark pixel.
+const FONT: [(char, Glyph); 18] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+    (' ', ["....", "....", "....", "....", "....", "...."]),
+];
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum OcrError {
+    WrongHeight(usize),
+    UnknownGlyph { position: usize, glyph: String },
+}
+
+/// Reads capital letters from a screen with lines of pixels. Every pixel
+/// equal to `lit` counts as lit, everything else as dark.
+pub fn read_letters(screen: &str, lit: char) -> Result<String, OcrError> {
+    let rows: Vec<Vec<bool>> = screen
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().map(|pixel| pixel == lit).collect())
+        .collect();
+
+    read_letters_from_pixels(&rows)
+}
+
+/// Same as `read_letters` but for rows of pixels, true being lit.
+pub fn read_letters_from_pixels(rows: &[Vec<bool>]) -> Result<String, OcrError> {
+    if rows.len() != GLYPH_HEIGHT {
+        return Err(OcrError::WrongHeight(rows.len()));
+    }
+
+    let width = rows.iter().map(|row| row.len()).max().unwrap_or_default();
+    let cell_width = GLYPH_WIDTH + GLYPH_SPACING;
+    let number_of_glyphs = width.div_ceil(cell_width);
+
+    (0..number_of_glyphs)
+        .map(|position| {
+            let start = position * cell_width;
+            let glyph: Vec<String> = rows
+                .iter()
+                .map(|row| {
+                    (start..(start + GLYPH_WIDTH))
+                        .map(|x| match row.get(x) {
+                            Some(true) => '#',
+                            _ => '.',
+                        })
+                        .collect()
+                })
+                .collect();
+
+            match_glyph(&glyph).ok_or_else(|| OcrError::UnknownGlyph {
+                position,
+                glyph: glyph.join("\n"),
+            })
+        })
+        .collect()
+}
+
+fn match_glyph(glyph: &[String]) -> Option<char> {
+    FONT.iter()
+        .find(|(_, known)| known.iter().zip(glyph).all(|(left, right)| left == right))
+        .map(|(letter, _)| *letter)
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OcrError::WrongHeight(height) => write!(
+                f,
+                "Screen has {} rows but letters are {} rows high",
+                height, GLYPH_HEIGHT
+            ),
+            OcrError::UnknownGlyph { position, glyph } => {
+                write!(f, "Unknown letter at position {}:\n{}", position, glyph)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_read_letters() {
+        // Set up
+        let input = "\
+#..#..###
+#..#...#.
+####...#.
+#..#...#.
+#..#...#.
+#..#..###
+";
+
+        // Act
+        let actual = read_letters(input, '#');
+
+        // Assert
+        assert_eq!(Ok("HI".to_string()), actual);
+    }
+
+    #[test]
+    fn test_unknown_glyph() {
+        // Set up
+        let input = "#...\n.#..\n..#.\n...#\n..#.\n.#..\n";
+
+        // Act
+        let actual = read_letters(input, '#');
+
+        // Assert
+        assert_eq!(
+            Err(OcrError::UnknownGlyph {
+                position: 0,
+                glyph: input.trim_end().to_string()
+            }),
+            actual
+        );
+    }
+}
@@ -1,50 +1,351 @@
-use std::ops;
-pub fn get_hcf<T>(left: T, right: T) -> T
-where
-    T: ops::Div<Output = T>
-        + ops::Mul<Output = T>
-        + ops::Sub<Output = T>
-        + Eq
-        + Default
-        + Copy
-        + PartialOrd,
-{
-    let mut current_left;
-    let mut current_right;
-
-    if left >= right {
-        current_left = left;
-        current_right = right;
+use std::fmt::Debug;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MathError {
+    Overflow,
+    /// Modulus is zero or negative.
+    InvalidModulus,
+    NotInvertible,
+    /// Congruences of the chinese remainder theorem contradict each other.
+    NoSolution,
+}
+
+/// Whole numbers the functions of this module work with. Every operation
+/// which could leave the range of the type is checked.
+pub trait Integer: Copy + Ord + Debug {
+    const ZERO: Self;
+    const ONE: Self;
+    const TWO: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_div(self, other: Self) -> Option<Self>;
+    fn checked_rem(self, other: Self) -> Option<Self>;
+    /// Remainder which is never negative.
+    fn checked_rem_euclid(self, other: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn checked_abs(self) -> Option<Self>;
+}
+
+/// Integers which can be negative, needed for the coefficients of the
+/// extended euclidean algorithm.
+pub trait SignedInteger: Integer {}
+
+macro_rules! impl_integer {
+    ($($number:ty),*) => {
+        $(
+            impl Integer for $number {
+                const ZERO: Self = 0;
+                const ONE: Self = 1;
+                const TWO: Self = 2;
+
+                fn checked_add(self, other: Self) -> Option<Self> {
+                    <$number>::checked_add(self, other)
+                }
+                fn checked_sub(self, other: Self) -> Option<Self> {
+                    <$number>::checked_sub(self, other)
+                }
+                fn checked_mul(self, other: Self) -> Option<Self> {
+                    <$number>::checked_mul(self, other)
+                }
+                fn checked_div(self, other: Self) -> Option<Self> {
+                    <$number>::checked_div(self, other)
+                }
+                fn checked_rem(self, other: Self) -> Option<Self> {
+                    <$number>::checked_rem(self, other)
+                }
+                fn checked_rem_euclid(self, other: Self) -> Option<Self> {
+                    <$number>::checked_rem_euclid(self, other)
+                }
+                fn checked_neg(self) -> Option<Self> {
+                    <$number>::checked_neg(self)
+                }
+                #[allow(unused_comparisons)]
+                fn checked_abs(self) -> Option<Self> {
+                    if self < 0 {
+                        <$number>::checked_neg(self)
+                    } else {
+                        Some(self)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+impl SignedInteger for i8 {}
+impl SignedInteger for i16 {}
+impl SignedInteger for i32 {}
+impl SignedInteger for i64 {}
+impl SignedInteger for i128 {}
+impl SignedInteger for isize {}
+
+/// Greatest common divisor, never negative. The gcd of zero and zero is zero.
+pub fn gcd<T: Integer>(left: T, right: T) -> Result<T, MathError> {
+    let mut left = left.checked_abs().ok_or(MathError::Overflow)?;
+    let mut right = right.checked_abs().ok_or(MathError::Overflow)?;
+
+    while right != T::ZERO {
+        let remainder = left.checked_rem(right).ok_or(MathError::Overflow)?;
+        left = right;
+        right = remainder;
+    }
+
+    Ok(left)
+}
+
+/// Least common multiple, never negative. Divides before it multiplies so it
+/// only fails if the result itself does not fit.
+pub fn lcm<T: Integer>(left: T, right: T) -> Result<T, MathError> {
+    if left == T::ZERO || right == T::ZERO {
+        return Ok(T::ZERO);
+    }
+
+    let divisor = gcd(left, right)?;
+    let left = left.checked_abs().ok_or(MathError::Overflow)?;
+    let right = right.checked_abs().ok_or(MathError::Overflow)?;
+
+    left.checked_div(divisor)
+        .and_then(|reduced| reduced.checked_mul(right))
+        .ok_or(MathError::Overflow)
+}
+
+/// Zero for no numbers at all.
+pub fn gcd_of<T: Integer>(numbers: impl IntoIterator<Item = T>) -> Result<T, MathError> {
+    numbers
+        .into_iter()
+        .try_fold(T::ZERO, |so_far, next| gcd(so_far, next))
+}
+
+/// One for no numbers at all.
+pub fn lcm_of<T: Integer>(numbers: impl IntoIterator<Item = T>) -> Result<T, MathError> {
+    numbers
+        .into_iter()
+        .try_fold(T::ONE, |so_far, next| lcm(so_far, next))
+}
+
+/// Returns (gcd, x, y) with left * x + right * y = gcd.
+pub fn extended_gcd<T: SignedInteger>(left: T, right: T) -> Result<(T, T, T), MathError> {
+    let (mut old_remainder, mut remainder) = (left, right);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+
+    while remainder != T::ZERO {
+        let quotient = old_remainder
+            .checked_div(remainder)
+            .ok_or(MathError::Overflow)?;
+        let next_step = |old: T, current: T| {
+            quotient
+                .checked_mul(current)
+                .and_then(|product| old.checked_sub(product))
+                .ok_or(MathError::Overflow)
+        };
+
+        (old_remainder, remainder) = (remainder, next_step(old_remainder, remainder)?);
+        (old_x, x) = (x, next_step(old_x, x)?);
+        (old_y, y) = (y, next_step(old_y, y)?);
+    }
+
+    if old_remainder < T::ZERO {
+        let negate = |number: T| number.checked_neg().ok_or(MathError::Overflow);
+        Ok((negate(old_remainder)?, negate(old_x)?, negate(old_y)?))
     } else {
-        current_left = right;
-        current_right = left;
+        Ok((old_remainder, old_x, old_y))
     }
+}
+
+/// Number x in 0..modulus with number * x = 1 under the modulus.
+pub fn mod_inverse<T: Integer>(number: T, modulus: T) -> Result<T, MathError> {
+    let number = normalize(number, modulus)?;
+
+    // Extended euclidean algorithm which only keeps the coefficient of the
+    // number, under the modulus so unsigned types work too.
+    let (mut old_remainder, mut remainder) = (number, modulus);
+    let (mut old_coefficient, mut coefficient) = (normalize(T::ONE, modulus)?, T::ZERO);
 
-    let mut prev_reamainder = current_left;
-    let mut remainder: T = current_left;
-    while remainder != Default::default() {
-        let mutiple = current_left / current_right;
-        prev_reamainder = remainder;
-        remainder = current_left - (mutiple * current_right);
+    while remainder != T::ZERO {
+        let quotient = old_remainder
+            .checked_div(remainder)
+            .ok_or(MathError::Overflow)?;
+        let next_remainder = quotient
+            .checked_mul(remainder)
+            .and_then(|product| old_remainder.checked_sub(product))
+            .ok_or(MathError::Overflow)?;
+        let next_coefficient = sub_mod(
+            old_coefficient,
+            mul_mod(normalize(quotient, modulus)?, coefficient, modulus)?,
+            modulus,
+        )?;
 
-        current_left = current_right;
-        current_right = remainder;
+        (old_remainder, remainder) = (remainder, next_remainder);
+        (old_coefficient, coefficient) = (coefficient, next_coefficient);
     }
 
-    prev_reamainder
+    if old_remainder == T::ONE {
+        Ok(old_coefficient)
+    } else {
+        Err(MathError::NotInvertible)
+    }
 }
 
-pub fn get_lcm<T>(left: T, right: T) -> T
-where
-    T: ops::Div<Output = T>
-        + ops::Mul<Output = T>
-        + ops::Sub<Output = T>
-        + Eq
-        + Default
-        + Copy
-        + PartialOrd,
-{
-    let product = left * right;
+/// Base to the power of exponent under the modulus. A negative exponent uses
+/// the inverse of the base.
+pub fn mod_pow<T: Integer>(base: T, exponent: T, modulus: T) -> Result<T, MathError> {
+    let (mut base, mut exponent) = if exponent < T::ZERO {
+        (
+            mod_inverse(base, modulus)?,
+            exponent.checked_neg().ok_or(MathError::Overflow)?,
+        )
+    } else {
+        (normalize(base, modulus)?, exponent)
+    };
 
-    product / get_hcf(left, right)
+    let mut result = normalize(T::ONE, modulus)?;
+    while exponent > T::ZERO {
+        if exponent.checked_rem(T::TWO) == Some(T::ONE) {
+            result = mul_mod(result, base, modulus)?;
+        }
+        base = mul_mod(base, base, modulus)?;
+        exponent = exponent.checked_div(T::TWO).ok_or(MathError::Overflow)?;
+    }
+
+    Ok(result)
+}
+
+/// Combines congruences given as (remainder, modulus) into one congruence
+/// (remainder, modulus) which all numbers satisfying every congruence fulfill.
+/// The moduli do not need to be coprime. No congruences at all give (0, 1).
+pub fn chinese_remainder<T: Integer>(
+    congruences: impl IntoIterator<Item = (T, T)>,
+) -> Result<(T, T), MathError> {
+    congruences.into_iter().try_fold(
+        (T::ZERO, T::ONE),
+        |(left_remainder, left_modulus), (right_remainder, right_modulus)| {
+            let right_remainder = normalize(right_remainder, right_modulus)?;
+            let divisor = gcd(left_modulus, right_modulus)?;
+            let combined_modulus = lcm(left_modulus, right_modulus)?;
+
+            // left_remainder + left_modulus * k = right_remainder under right_modulus
+            let difference = sub_mod(
+                right_remainder,
+                normalize(left_remainder, right_modulus)?,
+                right_modulus,
+            )?;
+            if difference.checked_rem(divisor) != Some(T::ZERO) {
+                return Err(MathError::NoSolution);
+            }
+
+            let reduced = |number: T| number.checked_div(divisor).ok_or(MathError::Overflow);
+            let reduced_right_modulus = reduced(right_modulus)?;
+            let k = mul_mod(
+                reduced(difference)?,
+                mod_inverse(reduced(left_modulus)?, reduced_right_modulus)?,
+                reduced_right_modulus,
+            )?;
+
+            let offset = left_modulus.checked_mul(k).ok_or(MathError::Overflow)?;
+            let remainder = add_mod(left_remainder, offset, combined_modulus)?;
+
+            Ok((remainder, combined_modulus))
+        },
+    )
+}
+
+fn normalize<T: Integer>(number: T, modulus: T) -> Result<T, MathError> {
+    if modulus <= T::ZERO {
+        return Err(MathError::InvalidModulus);
+    }
+
+    number
+        .checked_rem_euclid(modulus)
+        .ok_or(MathError::Overflow)
+}
+
+fn add_mod<T: Integer>(left: T, right: T, modulus: T) -> Result<T, MathError> {
+    let left = normalize(left, modulus)?;
+    let right = normalize(right, modulus)?;
+    let missing = modulus.checked_sub(right).ok_or(MathError::Overflow)?;
+
+    // Both are below the modulus, so this never leaves the range of the type.
+    if left >= missing {
+        left.checked_sub(missing).ok_or(MathError::Overflow)
+    } else {
+        left.checked_add(right).ok_or(MathError::Overflow)
+    }
+}
+
+fn sub_mod<T: Integer>(left: T, right: T, modulus: T) -> Result<T, MathError> {
+    let right = normalize(right, modulus)?;
+    let negated = modulus.checked_sub(right).ok_or(MathError::Overflow)?;
+
+    add_mod(left, negated, modulus)
+}
+
+/// Multiplies by doubling and adding, so no product larger than the modulus
+/// is ever needed.
+fn mul_mod<T: Integer>(left: T, right: T, modulus: T) -> Result<T, MathError> {
+    let mut doubled = normalize(left, modulus)?;
+    let mut times = normalize(right, modulus)?;
+    let mut result = T::ZERO;
+
+    while times > T::ZERO {
+        if times.checked_rem(T::TWO) == Some(T::ONE) {
+            result = add_mod(result, doubled, modulus)?;
+        }
+        doubled = add_mod(doubled, doubled, modulus)?;
+        times = times.checked_div(T::TWO).ok_or(MathError::Overflow)?;
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_gcd_and_lcm_over_numbers() {
+        // Act
+        let gcd_actual = gcd_of([84, -36, 120]);
+        let lcm_actual = lcm_of([23u64, 19, 13, 17]);
+        let overflow = lcm_of([u8::MAX, 2]);
+
+        // Assert
+        assert_eq!(Ok(12), gcd_actual);
+        assert_eq!(Ok(96577), lcm_actual);
+        assert_eq!(Err(MathError::Overflow), overflow);
+    }
+
+    #[test]
+    fn test_modular_arithmetic() {
+        // Act
+        let extended = extended_gcd(240i64, 46);
+        let inverse = mod_inverse(3u8, 250);
+        let power = mod_pow(4u64, 13, 497);
+        let near_max = mod_pow(u64::MAX - 1, 2, u64::MAX);
+
+        // Assert
+        assert_eq!(Ok((2, -9, 47)), extended);
+        assert_eq!(Ok(167), inverse);
+        assert_eq!(Err(MathError::NotInvertible), mod_inverse(4, 250));
+        assert_eq!(Ok(445), power);
+        assert_eq!(Ok(1), near_max);
+    }
+
+    #[test]
+    fn test_chinese_remainder() {
+        // Act
+        let coprime = chinese_remainder([(2u32, 3), (3, 5), (2, 7)]);
+        let not_coprime = chinese_remainder([(3i32, 4), (5, 6)]);
+        let contradiction = chinese_remainder([(1i32, 4), (2, 6)]);
+
+        // Assert
+        assert_eq!(Ok((23, 105)), coprime);
+        assert_eq!(Ok((11, 12)), not_coprime);
+        assert_eq!(Err(MathError::NoSolution), contradiction);
+    }
 }
@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
@@ -6,7 +6,7 @@ pub struct ChallangeArgs {
     /// The input to which a result is to be calculated
     pub input: String,
     /// If provided, the parameter input will be treated as a path to file.
-    /// the content of the file is used as input.
+    /// the content of the file is used as input. The path - reads from stdin.
     #[arg(short, long)]
     pub input_as_path: bool,
     /// Number of day under which the given task is given
@@ -15,4 +15,82 @@ pub struct ChallangeArgs {
     /// Which task is given under a given day
     #[arg(short, long)]
     pub task: u32,
+    /// Prints what happens in every step before the result.
+    /// Only supported by day 10 for now.
+    #[arg(long)]
+    pub trace: bool,
+    /// Cycles after which a trace or an annotation stops, for programs which
+    /// never terminate
+    #[arg(long, default_value_t = 10_000)]
+    pub max_cycles: usize,
+    /// Number of pixel rows of the crt of day 10
+    #[arg(long, default_value_t = 6)]
+    pub crt_height: usize,
+    /// Number of pixels in one row of the crt of day 10
+    #[arg(long, default_value_t = 40)]
+    pub crt_width: usize,
+    /// How many pixels the sprite of day 10 covers
+    #[arg(long, default_value_t = 3)]
+    pub sprite_width: usize,
+    /// How the screen of day 10 is printed. Pbm and pgm print only the image
+    /// so it can be redirected into a file.
+    #[arg(long, value_enum, default_value_t = CrtFormat::Text)]
+    pub crt_format: CrtFormat,
+    /// Character for a lit pixel in the text format and in a given drawing
+    #[arg(long, default_value_t = '#')]
+    pub lit_pixel: char,
+    /// Character for a dark pixel in the text format
+    #[arg(long, default_value_t = '.')]
+    pub dark_pixel: char,
+    /// Rounds after which the state of the monkeys of day 11 is printed,
+    /// separated by commas like 1,20,1000
+    #[arg(long, value_delimiter = ',')]
+    pub report_rounds: Vec<usize>,
+    /// Number of rounds the monkeys of day 11 play. Every task has its own
+    /// default like 20 for task 1.
+    #[arg(long)]
+    pub rounds: Option<usize>,
+    /// Number of most active monkeys of day 11 whose inspections are multiplied
+    #[arg(long, default_value_t = 2)]
+    pub top: usize,
+    /// Worry levels of day 11 are divided by this after an inspection in task 1
+    #[arg(long, default_value_t = 3)]
+    pub relief_divisor: usize,
+    /// Number of different symbols forming a marker of day 6. Defaults to 4
+    /// for packets and 14 for messages.
+    #[arg(long)]
+    pub marker_size: Option<usize>,
+    /// Size of the markers between two frames of day 6 task 4
+    #[arg(long, default_value_t = 14)]
+    pub delimiter_marker_size: usize,
+    /// Path to a file with the rules of the game of day 2, see
+    /// day_02::PUZZLE_RULES for the format
+    #[arg(long)]
+    pub rules: Option<String>,
+    /// Number of compartments of a rucksack of day 3
+    #[arg(long, default_value_t = 2)]
+    pub compartments: usize,
+    /// Number of rucksacks in a group sharing a badge of day 3
+    #[arg(long, default_value_t = 3)]
+    pub group_size: usize,
+    /// Which items of day 3 have which priority
+    #[arg(long, value_enum, default_value_t = Priorities::Letters)]
+    pub priorities: Priorities,
+    /// Items of day 3 in the order of their priority, replaces --priorities
+    #[arg(long)]
+    pub priority_table: Option<String>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrtFormat {
+    Text,
+    Blocks,
+    Pbm,
+    Pgm,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Priorities {
+    Letters,
+    Digits,
 }
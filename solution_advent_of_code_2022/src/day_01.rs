@@ -1,8 +1,13 @@
 use crate::challenge_args::ChallangeArgs;
+use crate::parsing::{self, StreamError};
+use std::io::BufRead;
 use std::num::ParseIntError;
 
 type ElfCalorie = Vec<u32>;
 type ElvesCalories = Vec<ElfCalorie>;
+/// Calories summed over many items, wider than a single item so long
+/// streams do not overflow.
+pub type TotalCalories = u64;
 
 pub fn get_top_calorie(args: &ChallangeArgs) -> Result<u32, ParseIntError> {
     let grouped_cals = parse_input(&args.input)?;
@@ -16,10 +21,46 @@ pub fn get_total_cal_top(args: &ChallangeArgs, number_top: usize) -> Result<u32,
     Ok(calor_sum.into_iter().take(number_top).sum())
 }
 
+/// Same as `get_total_cal_top` but keeps only the calories of the current
+/// elf and of the top elves in memory.
+pub fn get_total_cal_top_from_reader(
+    reader: impl BufRead,
+    number_top: usize,
+) -> Result<TotalCalories, StreamError> {
+    let mut top: Vec<TotalCalories> = Vec::with_capacity(number_top + 1);
+    let mut current_elve: Option<TotalCalories> = None;
+
+    parsing::for_each_line(reader, |line| {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            if let Some(calories) = current_elve.take() {
+                keep_if_top(&mut top, calories, number_top);
+            }
+        } else {
+            let item: u32 = parsing::try_parse(trimmed)?;
+            current_elve = Some(current_elve.unwrap_or_default() + TotalCalories::from(item));
+        }
+
+        Ok(())
+    })?;
+
+    if let Some(calories) = current_elve {
+        keep_if_top(&mut top, calories, number_top);
+    }
+
+    Ok(top.into_iter().sum())
+}
+
+fn keep_if_top(top: &mut Vec<TotalCalories>, calories: TotalCalories, number_top: usize) {
+    let position = top.partition_point(|kept| *kept >= calories);
+    top.insert(position, calories);
+    top.truncate(number_top);
+}
+
 fn get_calories_descending(grouped_cals: &ElvesCalories) -> ElfCalorie {
     let mut calor_sum: ElfCalorie = grouped_cals
-        .into_iter()
-        .map(|elve_calor| elve_calor.into_iter().sum())
+        .iter()
+        .map(|elve_calor| elve_calor.iter().sum())
         .collect();
 
     calor_sum.sort();
@@ -29,41 +70,27 @@ fn get_calories_descending(grouped_cals: &ElvesCalories) -> ElfCalorie {
 }
 
 fn get_max_calorie(get_max_from: &ElvesCalories) -> u32 {
-    return get_max_from.into_iter().fold(0u32, |akk, elve_calor| {
-        let sum_calorien = elve_calor.into_iter().sum();
+    get_max_from.iter().fold(0u32, |akk, elve_calor| {
+        let sum_calorien = elve_calor.iter().sum();
 
         if akk < sum_calorien {
             sum_calorien
         } else {
             akk
         }
-    });
+    })
 }
 
 fn parse_input(input: &str) -> Result<ElvesCalories, ParseIntError> {
-    let mut parsed: ElvesCalories = Vec::new();
-    let mut current_elve: ElfCalorie = Vec::new();
-
-    for line in input.lines() {
-        let trimmed = line.trim();
-        if trimmed == "" {
-            parsed.push(current_elve);
-            current_elve = Vec::new();
-        } else {
-            let item: u32 = match trimmed.parse() {
-                Ok(value) => value,
-                Err(error) => return Err(error),
-            };
-
-            current_elve.push(item);
-        }
-    }
-
-    if !current_elve.is_empty() {
-        parsed.push(current_elve);
-    }
-
-    Ok(parsed)
+    parsing::split_blocks(input)
+        .into_iter()
+        .map(|elve| {
+            elve.lines
+                .into_iter()
+                .map(|line| line.trim().parse())
+                .collect()
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -82,6 +109,20 @@ mod testing {
         // Assert
         assert_eq!(16_500, actual);
     }
+    #[test]
+    fn test_get_total_cal_top_from_reader() {
+        // Set up
+        let input = "1000\n2000\n\n4000\n  \n500\n\n\n3500\n";
+
+        // Act
+        let actual = get_total_cal_top_from_reader(input.as_bytes(), 2);
+        let beyond_u32 = get_total_cal_top_from_reader("4294967295\n1\n".as_bytes(), 1);
+
+        // Assert
+        assert_eq!(7500, actual.unwrap());
+        assert_eq!(4_294_967_296, beyond_u32.unwrap());
+    }
+
     #[test]
     fn test_get_max_calorie_result() -> Result<(), String> {
         const EXPECTED: u32 = 20_000;
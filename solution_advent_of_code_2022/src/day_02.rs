@@ -1,155 +1,693 @@
-// A rock => 1
-// B paper => 2
-// C scissors => 3
-//
-// X Rock => lose
-// Y paper => draw
-// Z scissors => win
-//
-
-pub fn calc_score_of_strat(input: &str) -> u32 {
-    let parsed = parsed_input(input);
-    let total_score = parsed
-        .iter()
-        .fold(0u32, |total, round| total + calc_one_round(&round));
-    total_score
+use crate::parsing::combinators::{
+    alt, integer, literal, map, parse_whole, spaces, token, trimmed, try_map, tuple, value, Parser,
+};
+use crate::parsing::{self, ParseError, StreamError};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::io::BufRead;
+
+type MoveIndex = usize;
+/// Sum over all rounds, wider than a single score so long guides do not
+/// overflow.
+pub type TotalScore = u64;
+
+/// Rules of the puzzle. Lines are either
+/// "move <name> <score> <letter of opponent> <own letter>",
+/// "beats <winner> <loser>" or "outcome <win|draw|lose> <score> <letter>".
+/// Two moves where none beats the other end in a draw.
+pub const PUZZLE_RULES: &str = "\
+move rock 1 A X
+move paper 2 B Y
+move scissors 3 C Z
+beats rock scissors
+beats paper rock
+beats scissors paper
+outcome lose 0 X
+outcome draw 3 Y
+outcome win 6 Z
+";
+
+pub const ROCK_PAPER_SCISSORS_LIZARD_SPOCK_RULES: &str = "\
+move rock 1 A V
+move paper 2 B W
+move scissors 3 C X
+move lizard 4 D Y
+move spock 5 E Z
+beats scissors paper
+beats paper rock
+beats rock lizard
+beats lizard spock
+beats spock scissors
+beats scissors lizard
+beats lizard paper
+beats paper spock
+beats spock rock
+beats rock scissors
+outcome lose 0 X
+outcome draw 3 Y
+outcome win 6 Z
+";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Lose,
 }
 
-pub fn calc_score_outcome_strat(input: &str) -> u32 {
-    let parsed = parsed_input(input);
-    let total_score = parsed.iter().fold(0u32, |total, round| {
-        total + calc_round_supposed_outcome(&round)
-    });
-    total_score
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct GameMove {
+    name: String,
+    score: u32,
+    opponent_letter: String,
+    own_letter: String,
 }
 
-fn calc_round_supposed_outcome(round: &Round) -> u32 {
-    let opponent = PlayerMoves::from_letter(&round.0);
-    let counter = PlayerMoves::from_letter(&round.1);
+/// Moves, which move beats which one, scores and the letters of a strategy
+/// guide for a game like rock paper scissors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameRules {
+    moves: Vec<GameMove>,
+    beats: HashSet<(MoveIndex, MoveIndex)>,
+    outcome_scores: HashMap<Outcome, u32>,
+    outcome_letters: HashMap<String, Outcome>,
+}
 
-    let supposed_outcome = counter.get_supposed_outcome();
+/// What a strategy guide yields, where the second column is taken as the
+/// own move, and what could be achieved against the same opponent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrategyReport {
+    pub guide_total: TotalScore,
+    /// Always choosing the move with the highest score in a round.
+    pub best_total: TotalScore,
+    /// Always choosing the move with the lowest score in a round.
+    pub worst_total: TotalScore,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// Own letter and the name of the move it should stand for so the guide
+    /// scores the most. Every letter stands for a different move.
+    pub best_mapping: Vec<(String, String)>,
+    pub best_mapping_total: TotalScore,
+}
 
-    let supposed_move = match supposed_outcome {
-        OutcomeScores::Win => find_winning_move(&opponent),
-        OutcomeScores::Draw => opponent,
-        OutcomeScores::Lose => find_losing_move(&opponent),
-    };
+/// Move of the opponent in the first column of a strategy guide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opponent(pub MoveIndex);
 
-    supposed_outcome.get_score() + supposed_move.get_score()
+/// Second column of a strategy guide as the task reads it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Move(MoveIndex),
+    Outcome(Outcome),
 }
 
-fn calc_one_round(round: &Round) -> u32 {
-    let oppenent = PlayerMoves::from_letter(&round.0);
-    let counter = PlayerMoves::from_letter(&round.1);
-    let move_score = counter.get_score();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    OwnMove,
+    Outcome,
+}
 
-    if oppenent == counter {
-        return OutcomeScores::Draw.get_score() + move_score;
-    }
+enum RuleLine {
+    Move(GameMove),
+    Beats(String, String),
+    Outcome(Outcome, u32, String),
+}
 
-    let needed_counter = find_winning_move(&oppenent);
+pub fn calc_score_of_strat(input: &str) -> TotalScore {
+    calc_score_of_strat_with(input, &GameRules::puzzle())
+}
 
-    if needed_counter == counter {
-        OutcomeScores::Win.get_score() + move_score
-    } else {
-        OutcomeScores::Lose.get_score() + move_score
-    }
+pub fn calc_score_outcome_strat(input: &str) -> TotalScore {
+    calc_score_outcome_strat_with(input, &GameRules::puzzle())
 }
 
-fn find_losing_move(opponent: &PlayerMoves) -> PlayerMoves {
-    match opponent {
-        PlayerMoves::Paper => PlayerMoves::Rock,
-        PlayerMoves::Rock => PlayerMoves::Scissors,
-        PlayerMoves::Scissors => PlayerMoves::Paper,
-    }
+/// Second column is the own move.
+pub fn calc_score_of_strat_with(input: &str, rules: &GameRules) -> TotalScore {
+    sum_scores(input, rules, ColumnKind::OwnMove)
 }
 
-fn find_winning_move(opponent: &PlayerMoves) -> PlayerMoves {
-    match opponent {
-        PlayerMoves::Rock => PlayerMoves::Paper,
-        PlayerMoves::Paper => PlayerMoves::Scissors,
-        PlayerMoves::Scissors => PlayerMoves::Rock,
-    }
+/// Second column is the outcome the round has to end with.
+pub fn calc_score_outcome_strat_with(input: &str, rules: &GameRules) -> TotalScore {
+    sum_scores(input, rules, ColumnKind::Outcome)
 }
 
-const ROCK_SCORE: u32 = 1u32;
-const PAPER_SCORE: u32 = 2u32;
-const SCISSORS_SCORE: u32 = 3u32;
-#[derive(Eq, Hash, PartialEq, Debug, Copy, Clone)]
-enum PlayerMoves {
-    Rock,
-    Paper,
-    Scissors,
+/// Scores line by line without keeping the whole strategy in memory.
+pub fn calc_score_of_strat_from_reader(
+    reader: impl BufRead,
+    rules: &GameRules,
+) -> Result<TotalScore, StreamError> {
+    sum_scores_from_reader(reader, rules, ColumnKind::OwnMove)
 }
 
-const WIN_SCORE: u32 = 6u32;
-const DRAW_SCORE: u32 = 3u32;
-const LOSE_SCORE: u32 = 0u32;
-enum OutcomeScores {
-    Win,
-    Draw,
-    Lose,
+pub fn calc_score_outcome_strat_from_reader(
+    reader: impl BufRead,
+    rules: &GameRules,
+) -> Result<TotalScore, StreamError> {
+    sum_scores_from_reader(reader, rules, ColumnKind::Outcome)
+}
+
+pub fn analyse_strategy(input: &str, rules: &GameRules) -> StrategyReport {
+    let mut counts = RoundCounts::new(rules);
+    parse_rounds(input, rules, ColumnKind::OwnMove)
+        .unwrap_or_else(|error| panic!("{}", error))
+        .iter()
+        .for_each(|round| counts.add(round));
+
+    counts.into_report()
+}
+
+pub fn analyse_strategy_from_reader(
+    reader: impl BufRead,
+    rules: &GameRules,
+) -> Result<StrategyReport, StreamError> {
+    let round = round_parser(rules, ColumnKind::OwnMove);
+    let mut counts = RoundCounts::new(rules);
+    parsing::for_each_line(reader, |line| {
+        if let Some(parsed) = parse_round(&round, line)? {
+            counts.add(&parsed);
+        }
+        Ok(())
+    })?;
+
+    Ok(counts.into_report())
+}
+
+/// Every line is a letter of the opponent and a letter of the second column
+/// separated by whitespace. Letters are matched regardless of their case and
+/// blank lines are skipped.
+pub fn parse_rounds(
+    input: &str,
+    rules: &GameRules,
+    kind: ColumnKind,
+) -> Result<Vec<(Opponent, Column)>, ParseError> {
+    let round = round_parser(rules, kind);
+    let rounds = parsing::parse_each_line(input, |line| parse_round(&round, line))?;
+
+    Ok(rounds.into_iter().flatten().collect())
+}
+
+fn sum_scores(input: &str, rules: &GameRules, kind: ColumnKind) -> TotalScore {
+    parse_rounds(input, rules, kind)
+        .unwrap_or_else(|error| panic!("{}", error))
+        .iter()
+        .fold(0, |total, round| {
+            total + TotalScore::from(rules.score_of_round(round))
+        })
+}
+
+fn sum_scores_from_reader(
+    reader: impl BufRead,
+    rules: &GameRules,
+    kind: ColumnKind,
+) -> Result<TotalScore, StreamError> {
+    let round = round_parser(rules, kind);
+    let mut total_score: TotalScore = 0;
+    parsing::for_each_line(reader, |line| {
+        if let Some(parsed) = parse_round(&round, line)? {
+            total_score += TotalScore::from(rules.score_of_round(&parsed));
+        }
+        Ok(())
+    })?;
+
+    Ok(total_score)
 }
 
-impl OutcomeScores {
-    fn get_score(&self) -> u32 {
-        match self {
-            Self::Win => WIN_SCORE,
-            Self::Draw => DRAW_SCORE,
-            Self::Lose => LOSE_SCORE,
+impl GameRules {
+    pub fn puzzle() -> Self {
+        Self::from_config(PUZZLE_RULES).expect("Rules of the puzzle are valid")
+    }
+
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        Self::from_config(ROCK_PAPER_SCISSORS_LIZARD_SPOCK_RULES)
+            .expect("Rules of rock paper scissors lizard spock are valid")
+    }
+
+    /// Reads rules in the format of `PUZZLE_RULES`. Empty lines and lines
+    /// starting with # are ignored.
+    pub fn from_config(config: &str) -> Result<Self, ParseError> {
+        let mut rules = Self {
+            moves: Vec::new(),
+            beats: HashSet::new(),
+            outcome_scores: HashMap::new(),
+            outcome_letters: HashMap::new(),
+        };
+        let rule_line = rule_line_parser();
+
+        for (index, line) in config.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            parse_whole(&rule_line, line)
+                .and_then(|parsed| rules.add(parsed))
+                .map_err(|error| error.at_line(index + 1))?;
+        }
+
+        for outcome in [Outcome::Win, Outcome::Draw, Outcome::Lose] {
+            if !rules.outcome_scores.contains_key(&outcome) {
+                return Err(ParseError::new(format!("No score for {:?}", outcome)));
+            }
+        }
+
+        Ok(rules)
+    }
+
+    pub fn outcome_of(&self, own: MoveIndex, opponent: MoveIndex) -> Outcome {
+        if self.beats.contains(&(own, opponent)) {
+            Outcome::Win
+        } else if self.beats.contains(&(opponent, own)) {
+            Outcome::Lose
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    pub fn score_of(&self, own: MoveIndex, opponent: MoveIndex) -> u32 {
+        self.moves[own].score + self.outcome_scores[&self.outcome_of(own, opponent)]
+    }
+
+    /// Move with the highest score among all moves which lead to the outcome.
+    pub fn move_for_outcome(&self, opponent: MoveIndex, outcome: Outcome) -> Option<MoveIndex> {
+        (0..self.moves.len())
+            .filter(|own| self.outcome_of(*own, opponent) == outcome)
+            .max_by_key(|own| self.moves[*own].score)
+    }
+
+    fn add(&mut self, line: RuleLine) -> Result<(), ParseError> {
+        match line {
+            RuleLine::Move(game_move) => {
+                if self
+                    .find_move(|known| known.name == game_move.name)
+                    .is_some()
+                {
+                    return Err(ParseError::new(format!(
+                        "Move {} is defined twice",
+                        game_move.name
+                    )));
+                }
+                if self
+                    .find_move(|known| {
+                        known
+                            .opponent_letter
+                            .eq_ignore_ascii_case(&game_move.opponent_letter)
+                            || known.own_letter.eq_ignore_ascii_case(&game_move.own_letter)
+                    })
+                    .is_some()
+                {
+                    return Err(ParseError::new(format!(
+                        "Letters of move {} are already taken",
+                        game_move.name
+                    )));
+                }
+                self.moves.push(game_move);
+            }
+            RuleLine::Beats(winner, loser) => {
+                let winner = self.move_by_name(&winner)?;
+                let loser = self.move_by_name(&loser)?;
+                if winner == loser || self.beats.contains(&(loser, winner)) {
+                    return Err(ParseError::new(format!(
+                        "{} and {} can not beat each other",
+                        self.moves[winner].name, self.moves[loser].name
+                    )));
+                }
+                self.beats.insert((winner, loser));
+            }
+            RuleLine::Outcome(outcome, score, letter) => {
+                if self.outcome_letters.iter().any(|(known, known_outcome)| {
+                    known.eq_ignore_ascii_case(&letter) && *known_outcome != outcome
+                }) {
+                    return Err(ParseError::new(format!(
+                        "Letter {} is already taken by another outcome",
+                        letter
+                    )));
+                }
+                self.outcome_scores.insert(outcome, score);
+                self.outcome_letters.insert(letter, outcome);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn find_move(&self, predicate: impl Fn(&GameMove) -> bool) -> Option<MoveIndex> {
+        self.moves.iter().position(predicate)
+    }
+
+    fn move_by_name(&self, name: &str) -> Result<MoveIndex, ParseError> {
+        self.find_move(|known| known.name == name)
+            .ok_or_else(|| ParseError::new(format!("Unknown move {}", name)))
+    }
+
+    fn opponent_move(&self, letter: &str) -> Result<Opponent, ParseError> {
+        self.find_move(|known| known.opponent_letter.eq_ignore_ascii_case(letter))
+            .map(Opponent)
+            .ok_or_else(|| ParseError::new(format!("No opponent move for letter {}", letter)))
+    }
+
+    fn column(&self, letter: &str, kind: ColumnKind) -> Result<Column, ParseError> {
+        match kind {
+            ColumnKind::OwnMove => self
+                .find_move(|known| known.own_letter.eq_ignore_ascii_case(letter))
+                .map(Column::Move)
+                .ok_or_else(|| ParseError::new(format!("No own move for letter {}", letter))),
+            ColumnKind::Outcome => self
+                .outcome_letters
+                .iter()
+                .find(|(known, _)| known.eq_ignore_ascii_case(letter))
+                .map(|(_, outcome)| Column::Outcome(*outcome))
+                .ok_or_else(|| ParseError::new(format!("No outcome for letter {}", letter))),
         }
     }
+
+    /// Checked while parsing so every parsed round can be scored.
+    fn check_round(&self, round: (Opponent, Column)) -> Result<(Opponent, Column), ParseError> {
+        if let (Opponent(opponent), Column::Outcome(outcome)) = round {
+            if self.move_for_outcome(opponent, outcome).is_none() {
+                return Err(ParseError::new(format!(
+                    "No move leads to {:?} against {}",
+                    outcome, self.moves[opponent].name
+                )));
+            }
+        }
+
+        Ok(round)
+    }
+
+    fn score_of_round(&self, round: &(Opponent, Column)) -> u32 {
+        let (Opponent(opponent), column) = *round;
+        let own = match column {
+            Column::Move(own) => own,
+            Column::Outcome(outcome) => self
+                .move_for_outcome(opponent, outcome)
+                .expect("Rounds are checked while parsing"),
+        };
+
+        self.score_of(own, opponent)
+    }
+}
+
+/// How often each own letter was played against each opponent move. Rows are
+/// opponent moves and columns the moves of the own letters.
+struct RoundCounts<'a> {
+    rules: &'a GameRules,
+    counts: Vec<Vec<TotalScore>>,
 }
-impl PlayerMoves {
-    fn from_letter(letter: &str) -> Self {
-        match letter {
-            "A" => Self::Rock,
-            "B" => Self::Paper,
-            "C" => Self::Scissors,
-            "X" => Self::Rock,
-            "Y" => Self::Paper,
-            "Z" => Self::Scissors,
-            _ => panic!("No player move exits for letter {}", letter),
+
+impl<'a> RoundCounts<'a> {
+    fn new(rules: &'a GameRules) -> Self {
+        let number_of_moves = rules.moves.len();
+        Self {
+            rules,
+            counts: vec![vec![0; number_of_moves]; number_of_moves],
         }
     }
 
-    fn get_score(&self) -> u32 {
-        match self {
-            Self::Rock => ROCK_SCORE,
-            Self::Paper => PAPER_SCORE,
-            Self::Scissors => SCISSORS_SCORE,
+    fn add(&mut self, round: &(Opponent, Column)) {
+        match *round {
+            (Opponent(opponent), Column::Move(column)) => self.counts[opponent][column] += 1,
+            (_, Column::Outcome(_)) => unreachable!("Rounds are parsed with own moves"),
         }
     }
 
-    fn get_supposed_outcome(&self) -> OutcomeScores {
-        // X Rock => lose
-        // Y paper => draw
-        // Z scissors => win
-        match self {
-            Self::Rock => OutcomeScores::Lose,
-            Self::Paper => OutcomeScores::Draw,
-            Self::Scissors => OutcomeScores::Win,
+    /// Total if the own letter of column i stands for the move mapping[i].
+    fn total_with(&self, mapping: &[MoveIndex]) -> TotalScore {
+        self.counts
+            .iter()
+            .enumerate()
+            .flat_map(|(opponent, row)| {
+                row.iter().enumerate().map(move |(column, amount)| {
+                    amount * TotalScore::from(self.rules.score_of(mapping[column], opponent))
+                })
+            })
+            .sum()
+    }
+
+    fn total_per_round(&self, choose: impl Fn(u32, u32) -> u32) -> TotalScore {
+        let rules = self.rules;
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(opponent, row)| {
+                let chosen = (0..rules.moves.len())
+                    .map(|own| rules.score_of(own, opponent))
+                    .reduce(&choose)
+                    .unwrap_or(0);
+                row.iter().sum::<TotalScore>() * TotalScore::from(chosen)
+            })
+            .sum()
+    }
+
+    fn into_report(self) -> StrategyReport {
+        let rules = self.rules;
+        let identity: Vec<MoveIndex> = (0..rules.moves.len()).collect();
+
+        let (mut wins, mut draws, mut losses) = (0, 0, 0);
+        for (opponent, row) in self.counts.iter().enumerate() {
+            for (own, amount) in row.iter().enumerate() {
+                let amount = *amount as usize;
+                match rules.outcome_of(own, opponent) {
+                    Outcome::Win => wins += amount,
+                    Outcome::Draw => draws += amount,
+                    Outcome::Lose => losses += amount,
+                }
+            }
         }
+
+        let mut best_mapping = identity.clone();
+        let mut best_mapping_total = self.total_with(&identity);
+        for_each_permutation(&mut identity.clone(), 0, &mut |mapping| {
+            let total = self.total_with(mapping);
+            if total > best_mapping_total {
+                best_mapping_total = total;
+                best_mapping = mapping.to_vec();
+            }
+        });
+
+        StrategyReport {
+            guide_total: self.total_with(&identity),
+            best_total: self.total_per_round(u32::max),
+            worst_total: self.total_per_round(u32::min),
+            wins,
+            draws,
+            losses,
+            best_mapping: best_mapping
+                .iter()
+                .enumerate()
+                .map(|(column, game_move)| {
+                    (
+                        rules.moves[column].own_letter.clone(),
+                        rules.moves[*game_move].name.clone(),
+                    )
+                })
+                .collect(),
+            best_mapping_total,
+        }
+    }
+}
+
+fn for_each_permutation(
+    items: &mut [MoveIndex],
+    fixed: usize,
+    on_next: &mut impl FnMut(&[MoveIndex]),
+) {
+    if fixed == items.len() {
+        on_next(items);
+        return;
+    }
+
+    for next in fixed..items.len() {
+        items.swap(fixed, next);
+        for_each_permutation(items, fixed + 1, on_next);
+        items.swap(fixed, next);
     }
 }
 
-#[derive(Debug)]
-struct Round(String, String);
-type ParsedInput = Vec<Round>;
-fn parsed_input(to_parse: &str) -> ParsedInput {
-    let mut parsed: ParsedInput = Vec::new();
-    for line in to_parse.lines() {
-        let mut left_right = line.split(' ');
-        let left = left_right.next();
-        let right = left_right.next();
-        parsed.push(Round(
-            left.expect("Did not find oppenents move as single letter")
-                .to_owned(),
-            right
-                .expect("Did not find move to make as single letter")
-                .to_owned(),
-        ));
+impl Display for StrategyReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Score following the guide: {}", self.guide_total)?;
+        writeln!(
+            f,
+            "Wins: {}, draws: {}, losses: {}",
+            self.wins, self.draws, self.losses
+        )?;
+        writeln!(f, "Best possible score: {}", self.best_total)?;
+        writeln!(f, "Worst possible score: {}", self.worst_total)?;
+        let mapping: Vec<String> = self
+            .best_mapping
+            .iter()
+            .map(|(letter, name)| format!("{} => {}", letter, name))
+            .collect();
+        write!(
+            f,
+            "Best meaning of the letters: {} with a score of {}",
+            mapping.join(", "),
+            self.best_mapping_total
+        )
     }
+}
+
+fn rule_line_parser() -> impl Parser<RuleLine> {
+    let game_move = map(
+        tuple((
+            literal("move"),
+            spaces(),
+            tuple((token(), spaces(), integer::<u32>())),
+            spaces(),
+            tuple((token(), spaces(), token())),
+        )),
+        |(_, _, (name, _, score), _, (opponent_letter, _, own_letter))| {
+            RuleLine::Move(GameMove {
+                name,
+                score,
+                opponent_letter,
+                own_letter,
+            })
+        },
+    );
+    let beats = map(
+        tuple((literal("beats"), spaces(), token(), spaces(), token())),
+        |(_, _, winner, _, loser)| RuleLine::Beats(winner, loser),
+    );
+    let outcome = alt((
+        value(literal("win"), Outcome::Win),
+        value(literal("draw"), Outcome::Draw),
+        value(literal("lose"), Outcome::Lose),
+    ));
+    let outcome_score = map(
+        tuple((
+            literal("outcome"),
+            spaces(),
+            outcome,
+            spaces(),
+            tuple((integer::<u32>(), spaces(), token())),
+        )),
+        |(_, _, outcome, _, (score, _, letter))| RuleLine::Outcome(outcome, score, letter),
+    );
+
+    alt((game_move, beats, outcome_score))
+}
+
+/// None for a line which is empty or only whitespace.
+fn parse_round(
+    round: &impl Parser<(Opponent, Column)>,
+    line: &str,
+) -> Result<Option<(Opponent, Column)>, ParseError> {
+    if line.trim().is_empty() {
+        return Ok(None);
+    }
+
+    parse_whole(round, line).map(Some)
+}
+
+fn round_parser(rules: &GameRules, kind: ColumnKind) -> impl Parser<(Opponent, Column)> + '_ {
+    try_map(
+        trimmed(tuple((token(), spaces(), token()))),
+        move |(opponent, _, column)| {
+            rules.check_round((
+                rules.opponent_move(&opponent)?,
+                rules.column(&column, kind)?,
+            ))
+        },
+    )
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_puzzle_rules() {
+        // Set up
+        let input = "A Y\nB X\nC Z\n";
+
+        // Act
+        let own_moves = calc_score_of_strat(input);
+        let outcomes = calc_score_outcome_strat(input);
+
+        // Assert
+        assert_eq!(15, own_moves);
+        assert_eq!(12, outcomes);
+    }
+
+    #[test]
+    fn test_rock_paper_scissors_lizard_spock() {
+        // Set up
+        let rules = GameRules::rock_paper_scissors_lizard_spock();
+        let input = "A Y\nE X\nD Z\n";
+
+        // Act
+        let own_moves = calc_score_of_strat_with(input, &rules);
+        let outcomes = calc_score_outcome_strat_with(input, &rules);
+
+        // Assert
+        // Lizard loses against rock, scissors against spock, spock against lizard.
+        assert_eq!(4 + 3 + 5, own_moves);
+        // Draw by rock, losing to spock and beating lizard by scissors
+        // which scores more than rock.
+        assert_eq!(4 + 3 + 9, outcomes);
+    }
+
+    #[test]
+    fn test_analyse_strategy() {
+        // Set up
+        let input = "A Y\nB X\nC Z\n";
 
-    parsed
+        // Act
+        let actual = analyse_strategy(input, &GameRules::puzzle());
+
+        // Assert
+        assert_eq!(
+            StrategyReport {
+                guide_total: 15,
+                best_total: 24,
+                worst_total: 6,
+                wins: 1,
+                draws: 1,
+                losses: 1,
+                best_mapping: vec![
+                    ("X".to_string(), "scissors".to_string()),
+                    ("Y".to_string(), "paper".to_string()),
+                    ("Z".to_string(), "rock".to_string()),
+                ],
+                best_mapping_total: 24,
+            },
+            actual
+        );
+    }
+
+    #[test]
+    fn test_parse_rounds() {
+        // Set up
+        let rules = GameRules::puzzle();
+
+        // Act
+        let tolerant = parse_rounds("  a \t y\n\nC   Z  \n \t\n", &rules, ColumnKind::Outcome);
+        let invalid = parse_rounds("A Y\nB Q\n", &rules, ColumnKind::OwnMove);
+        let streamed = calc_score_of_strat_from_reader("A Y\n  \nB X\nC Z\n\n".as_bytes(), &rules);
+
+        // Assert
+        assert_eq!(
+            Ok(vec![
+                (Opponent(0), Column::Outcome(Outcome::Draw)),
+                (Opponent(2), Column::Outcome(Outcome::Win)),
+            ]),
+            tolerant
+        );
+        assert_eq!(Some(2), invalid.unwrap_err().line);
+        assert_eq!(Ok(15), streamed.map_err(|error| error.to_string()));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        // Act
+        let actual = GameRules::from_config("move rock 1 A X\nbeats rock paper\n");
+        let same_letter = GameRules::from_config("move rock 1 A X\nmove paper 2 a Y\n");
+
+        // Assert
+        assert_eq!(Some(2), actual.unwrap_err().line);
+        assert_eq!(Some(2), same_letter.unwrap_err().line);
+    }
 }
@@ -1,164 +1,308 @@
-use std::collections::HashSet;
+use std::fmt::Display;
 use std::ops;
 
 type AllRucksacks = Vec<Rucksack>;
-type FoundItems = HashSet<char>;
-type RucksackNoComparment = String;
-type Compartment = String;
-
-#[derive(Debug)]
-struct FoundGroupBadges(Vec<FoundItemDuplicates>);
-#[derive(Debug)]
-struct Rucksack(Compartment, Compartment);
-#[derive(Debug)]
-struct RucksackGroup(Vec<[RucksackNoComparment; 3]>);
-#[derive(Debug)]
-struct FoundItemDuplicates(Vec<char>);
 
 const UPPER_CASE_NUM_OFFSET: u32 = 27u32;
 const LOWER_CASE_NUM_OFFSET: u32 = 1u32;
+const DIGIT_NUM_OFFSET: u32 = 1u32;
 const UPPER_A_NUM: u32 = 'A' as u32;
 const LOWER_A_NUM: u32 = 'a' as u32;
+const ZERO_NUM: u32 = '0' as u32;
+/// Item sets have one bit per priority.
+const MAX_PRIORITY: u32 = u64::BITS - 1;
+
+pub const DEFAULT_COMPARTMENTS: usize = 2;
+pub const DEFAULT_GROUP_SIZE: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RucksackError {
+    ZeroCompartments,
+    ZeroGroupSize,
+    InvalidItem {
+        line: usize,
+        item: char,
+    },
+    PriorityTooHigh {
+        item: char,
+        priority: u32,
+    },
+    UnevenCompartments {
+        line: usize,
+        length: usize,
+        compartments: usize,
+    },
+    UnevenGroups {
+        rucksacks: usize,
+        group_size: usize,
+    },
+    NoCommonBadge {
+        group: usize,
+    },
+    MultipleBadges {
+        group: usize,
+        badges: Vec<char>,
+    },
+}
+
+/// Maps items to their priority. Priorities have to be between 1 and 63.
+pub trait PriorityScheme {
+    fn priority_of(&self, item: char) -> Option<u32>;
+    fn item_of(&self, priority: u32) -> Option<char>;
+}
+
+/// a to z have priority 1 to 26 and A to Z 27 to 52 like in the puzzle.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LetterPriorities;
+
+/// 0 to 9 have priority 1 to 10.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DigitPriorities;
+
+/// Every item of the table has its position in the table, starting at 1, as
+/// its priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TablePriorities(Vec<char>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PriorityTableError {
+    Empty,
+    TooLong(usize),
+    Duplicate(char),
+}
+
+/// Items of a compartment or a rucksack, one bit for the priority of every
+/// item.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ItemSet(u64);
+
+/// Items of every compartment of one rucksack.
+#[derive(Debug)]
+struct Rucksack(Vec<ItemSet>);
 
 pub fn get_total_prio_of_dups(input: &str) -> u32 {
-    let parsed_input = parse_input(input);
-    let total = calc_duplicate_prios(&parsed_input);
-    total.0
+    calc_prio_of_duplicates(input, DEFAULT_COMPARTMENTS).unwrap_or_else(|error| panic!("{}", error))
 }
 
 pub fn get_total_prio_of_group_badges(input: &str) -> u32 {
-    let input_parsed = parse_input(input);
-    let grouped = transform_input_for_group_badges(input_parsed);
-    let group_badges = get_found_group_item(grouped);
-    let total = get_total_prio_grouped(&group_badges);
+    calc_prio_of_group_badges(input, DEFAULT_GROUP_SIZE).unwrap_or_else(|error| panic!("{}", error))
+}
+
+pub fn calc_prio_of_duplicates(input: &str, compartments: usize) -> Result<u32, RucksackError> {
+    calc_prio_of_duplicates_with(input, compartments, &LetterPriorities)
+}
 
-    total.0
+pub fn calc_prio_of_group_badges(input: &str, group_size: usize) -> Result<u32, RucksackError> {
+    calc_prio_of_group_badges_with(input, group_size, &LetterPriorities)
 }
 
-fn get_total_prio_grouped(group_badges: &FoundGroupBadges) -> Prio {
-    group_badges
-        .0
+/// Every item which is in more than one compartment of a rucksack counts once
+/// for this rucksack.
+pub fn calc_prio_of_duplicates_with(
+    input: &str,
+    compartments: usize,
+    scheme: &dyn PriorityScheme,
+) -> Result<u32, RucksackError> {
+    let parsed_input = parse_input(input, compartments, scheme)?;
+    let total = parsed_input
         .iter()
-        .fold(Prio::default(), |total, badges| {
-            let mut local_total = Prio::default();
-            for one_badge in &badges.0 {
-                let prio =
-                    convert_letter_to_prio(*one_badge).expect("Could not convert char to prio");
-                local_total += prio;
-            }
-            total + local_total
-        })
+        .fold(Prio::default(), |total, rucksack| {
+            total + find_duplicates(rucksack).total_prio()
+        });
+
+    Ok(total.0)
 }
 
-fn get_found_group_item(find_in: RucksackGroup) -> FoundGroupBadges {
-    FoundGroupBadges(
-        find_in
-            .0
-            .into_iter()
-            .map(|grouped| {
-                let found_in_1 = find_uniques_letters(&grouped[0]);
-                let found_in_2 = find_uniques_letters(&grouped[1]);
-                let found_in_3 = find_uniques_letters(&grouped[2]);
-
-                FoundItemDuplicates(
-                    found_in_1
-                        .intersection(&found_in_2)
-                        .cloned()
-                        .collect::<FoundItems>()
-                        .intersection(&found_in_3)
-                        .cloned()
-                        .collect(),
-                )
-            })
-            .collect(),
-    )
-}
-
-fn transform_input_for_group_badges(to_group: AllRucksacks) -> RucksackGroup {
-    RucksackGroup(
-        to_group
-            .chunks(3)
-            .map(|group_rucksack| {
-                if let [first, second, third] = group_rucksack {
-                    let combined_first = combine_compartments(&first.0, &first.1);
-                    let combined_second = combine_compartments(&second.0, &second.1);
-                    let combined_third = combine_compartments(&third.0, &third.1);
-
-                    return [combined_first, combined_second, combined_third];
-                }
-
-                panic!("Every item as a slice should have 3 elements")
-            })
-            .collect(),
-    )
-}
-
-fn combine_compartments(left: &Compartment, right: &Compartment) -> Compartment {
-    let mut left_owned = String::from(left);
-    left_owned.push_str(&right);
-    left_owned
-}
-
-fn calc_duplicate_prios(calc_from: &AllRucksacks) -> Prio {
-    calc_from
+/// Every group of `group_size` rucksacks in a row has to share exactly one
+/// item, their badge.
+pub fn calc_prio_of_group_badges_with(
+    input: &str,
+    group_size: usize,
+    scheme: &dyn PriorityScheme,
+) -> Result<u32, RucksackError> {
+    // Compartments do not matter for badges.
+    let parsed_input = parse_input(input, 1, scheme)?;
+    let mut total = Prio::default();
+    for (group, badge) in find_group_badges(&parsed_input, group_size)?
         .into_iter()
-        .fold(Prio::default(), |total, next_rucksack| {
-            let mut add_to_total = Prio::default();
-            let duplicates = find_first_duplicate(next_rucksack);
-            for one_dup in duplicates.0 {
-                let numeric_dup = convert_letter_to_prio(one_dup);
-                add_to_total += numeric_dup.expect("Could convert char");
-            }
+        .enumerate()
+    {
+        total += check_single_badge(badge, group + 1, scheme)?;
+    }
+
+    Ok(total.0)
+}
+
+fn find_group_badges(
+    rucksacks: &AllRucksacks,
+    group_size: usize,
+) -> Result<Vec<ItemSet>, RucksackError> {
+    if group_size == 0 {
+        return Err(RucksackError::ZeroGroupSize);
+    }
+    if !rucksacks.len().is_multiple_of(group_size) {
+        return Err(RucksackError::UnevenGroups {
+            rucksacks: rucksacks.len(),
+            group_size,
+        });
+    }
 
-            total + add_to_total
+    Ok(rucksacks
+        .chunks(group_size)
+        .map(|group| {
+            group
+                .iter()
+                .map(Rucksack::all_items)
+                .reduce(ItemSet::intersection)
+                .unwrap_or_default()
         })
+        .collect())
+}
+
+fn check_single_badge(
+    badge: ItemSet,
+    group: usize,
+    scheme: &dyn PriorityScheme,
+) -> Result<Prio, RucksackError> {
+    let mut priorities = badge.priorities();
+    match (priorities.next(), priorities.next()) {
+        (Some(prio), None) => Ok(prio),
+        (None, _) => Err(RucksackError::NoCommonBadge { group }),
+        _ => Err(RucksackError::MultipleBadges {
+            group,
+            badges: badge
+                .priorities()
+                .filter_map(|prio| scheme.item_of(prio.0))
+                .collect(),
+        }),
+    }
+}
+
+fn find_duplicates(rucksack: &Rucksack) -> ItemSet {
+    let mut seen = ItemSet::default();
+    let mut duplicates = ItemSet::default();
+    for compartment in &rucksack.0 {
+        duplicates = duplicates.union(seen.intersection(*compartment));
+        seen = seen.union(*compartment);
+    }
+
+    duplicates
 }
 
-fn find_first_duplicate(to_find_in: &Rucksack) -> FoundItemDuplicates {
-    let left_compartment = &to_find_in.0;
-    let right_compartment = &to_find_in.1;
+impl ItemSet {
+    fn from_items(
+        items: &[char],
+        line: usize,
+        scheme: &dyn PriorityScheme,
+    ) -> Result<Self, RucksackError> {
+        items.iter().try_fold(Self::default(), |set, item| {
+            let priority = scheme
+                .priority_of(*item)
+                .ok_or(RucksackError::InvalidItem { line, item: *item })?;
+            if priority > MAX_PRIORITY {
+                return Err(RucksackError::PriorityTooHigh {
+                    item: *item,
+                    priority,
+                });
+            }
+            Ok(Self(set.0 | 1 << priority))
+        })
+    }
+
+    fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
 
-    let found_in_left = find_uniques_letters(&left_compartment);
-    let found_in_right = find_uniques_letters(&right_compartment);
+    fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
 
-    let duplicates: FoundItemDuplicates = FoundItemDuplicates(
-        found_in_left
-            .intersection(&found_in_right)
-            .cloned()
-            .collect(),
-    );
+    fn priorities(self) -> impl Iterator<Item = Prio> {
+        (0..u64::BITS)
+            .filter(move |bit| self.0 & 1 << bit != 0)
+            .map(Prio)
+    }
 
-    return duplicates;
+    fn total_prio(self) -> Prio {
+        self.priorities()
+            .fold(Prio::default(), |total, prio| total + prio)
+    }
 }
 
-fn find_uniques_letters(compartment: &str) -> FoundItems {
-    let mut found: FoundItems = HashSet::with_capacity(compartment.len());
-    found.extend(compartment.chars());
-    found
+impl Rucksack {
+    fn all_items(&self) -> ItemSet {
+        self.0.iter().fold(ItemSet::default(), |all, compartment| {
+            all.union(*compartment)
+        })
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct Prio(u32);
-#[derive(Debug)]
-enum PrioConvertError {
-    IsNotAscii,
-    NotUpperOrLowerCaseLetter,
+
+impl PriorityScheme for LetterPriorities {
+    fn priority_of(&self, item: char) -> Option<u32> {
+        let numeric_value = item as u32;
+        if item.is_ascii_lowercase() {
+            Some(numeric_value - LOWER_A_NUM + LOWER_CASE_NUM_OFFSET)
+        } else if item.is_ascii_uppercase() {
+            Some(numeric_value - UPPER_A_NUM + UPPER_CASE_NUM_OFFSET)
+        } else {
+            None
+        }
+    }
+
+    fn item_of(&self, priority: u32) -> Option<char> {
+        let numeric_value = if priority >= UPPER_CASE_NUM_OFFSET {
+            priority - UPPER_CASE_NUM_OFFSET + UPPER_A_NUM
+        } else {
+            priority.checked_sub(LOWER_CASE_NUM_OFFSET)? + LOWER_A_NUM
+        };
+        char::from_u32(numeric_value).filter(char::is_ascii_alphabetic)
+    }
+}
+
+impl PriorityScheme for DigitPriorities {
+    fn priority_of(&self, item: char) -> Option<u32> {
+        item.to_digit(10).map(|digit| digit + DIGIT_NUM_OFFSET)
+    }
+
+    fn item_of(&self, priority: u32) -> Option<char> {
+        let digit = priority.checked_sub(DIGIT_NUM_OFFSET)?;
+        char::from_u32(digit + ZERO_NUM).filter(char::is_ascii_digit)
+    }
+}
+
+impl TablePriorities {
+    pub fn new(table: &str) -> Result<Self, PriorityTableError> {
+        let items: Vec<char> = table.chars().collect();
+        if items.is_empty() {
+            return Err(PriorityTableError::Empty);
+        }
+        if items.len() > MAX_PRIORITY as usize {
+            return Err(PriorityTableError::TooLong(items.len()));
+        }
+        for (index, item) in items.iter().enumerate() {
+            if items[..index].contains(item) {
+                return Err(PriorityTableError::Duplicate(*item));
+            }
+        }
+
+        Ok(Self(items))
+    }
 }
-fn convert_letter_to_prio(to_convert: char) -> Result<Prio, PrioConvertError> {
-    if !to_convert.is_ascii() {
-        return Err(PrioConvertError::IsNotAscii);
+
+impl PriorityScheme for TablePriorities {
+    fn priority_of(&self, item: char) -> Option<u32> {
+        self.0
+            .iter()
+            .position(|known| *known == item)
+            .map(|index| index as u32 + 1)
     }
-    let numeric_value = to_convert as u32;
-    if to_convert.is_lowercase() {
-        Ok(Prio(
-            numeric_value - (LOWER_A_NUM as u32) + LOWER_CASE_NUM_OFFSET,
-        ))
-    } else if to_convert.is_uppercase() {
-        Ok(Prio(
-            numeric_value - (UPPER_A_NUM as u32) + UPPER_CASE_NUM_OFFSET,
-        ))
-    } else {
-        Err(PrioConvertError::NotUpperOrLowerCaseLetter)
+
+    fn item_of(&self, priority: u32) -> Option<char> {
+        let index = priority.checked_sub(1)? as usize;
+        self.0.get(index).copied()
     }
 }
 
@@ -175,21 +319,179 @@ impl ops::AddAssign<Prio> for Prio {
     }
 }
 
-impl Default for Prio {
-    fn default() -> Self {
-        Self(0)
+fn parse_input(
+    to_convert: &str,
+    compartments: usize,
+    scheme: &dyn PriorityScheme,
+) -> Result<AllRucksacks, RucksackError> {
+    if compartments == 0 {
+        return Err(RucksackError::ZeroCompartments);
     }
-}
 
-fn parse_input(to_convert: &str) -> AllRucksacks {
     to_convert
         .lines()
-        .map(|next_line| {
-            let half_number_letters = next_line.len() / 2usize;
-            let left_compartment: String = next_line.chars().take(half_number_letters).collect();
-            let right_compartment: String = next_line.chars().skip(half_number_letters).collect();
+        .enumerate()
+        .map(|(index, next_line)| {
+            let line = index + 1;
+            let items: Vec<char> = next_line.trim_end().chars().collect();
+            if !items.len().is_multiple_of(compartments) {
+                return Err(RucksackError::UnevenCompartments {
+                    line,
+                    length: items.len(),
+                    compartments,
+                });
+            }
+
+            if items.is_empty() {
+                return Ok(Rucksack(vec![ItemSet::default(); compartments]));
+            }
+
+            let size_compartment = items.len() / compartments;
+            let sets = items
+                .chunks(size_compartment)
+                .map(|compartment| ItemSet::from_items(compartment, line, scheme))
+                .collect::<Result<Vec<ItemSet>, RucksackError>>()?;
 
-            Rucksack(left_compartment, right_compartment)
+            Ok(Rucksack(sets))
         })
         .collect()
 }
+
+impl Display for RucksackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RucksackError::ZeroCompartments => write!(f, "A rucksack needs a compartment"),
+            RucksackError::ZeroGroupSize => write!(f, "A group needs at least one rucksack"),
+            RucksackError::InvalidItem { line, item } => {
+                write!(f, "Line {}: {:?} is no valid item", line, item)
+            }
+            RucksackError::PriorityTooHigh { item, priority } => write!(
+                f,
+                "Priority {} of {:?} is higher than {}",
+                priority, item, MAX_PRIORITY
+            ),
+            RucksackError::UnevenCompartments {
+                line,
+                length,
+                compartments,
+            } => write!(
+                f,
+                "Line {}: {} items can not be split into {} compartments",
+                line, length, compartments
+            ),
+            RucksackError::UnevenGroups {
+                rucksacks,
+                group_size,
+            } => write!(
+                f,
+                "{} rucksacks can not be split into groups of {}",
+                rucksacks, group_size
+            ),
+            RucksackError::NoCommonBadge { group } => {
+                write!(f, "Group {} has no item in common", group)
+            }
+            RucksackError::MultipleBadges { group, badges } => write!(
+                f,
+                "Group {} has more than one item in common: {}",
+                group,
+                badges.iter().collect::<String>()
+            ),
+        }
+    }
+}
+
+impl Display for PriorityTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PriorityTableError::Empty => write!(f, "A priority table needs at least one item"),
+            PriorityTableError::TooLong(length) => write!(
+                f,
+                "Priority table with {} items has more than {}",
+                length, MAX_PRIORITY
+            ),
+            PriorityTableError::Duplicate(item) => {
+                write!(f, "{:?} is more than once in the priority table", item)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    const EXAMPLE: &str = "vJrwpWtwJgWrhcsFMMfFFhFp
+jqHRNqRjqzjGDLGLrsFMfFZSrLrFZsSL
+PmmdzqPrVvPwwTWBwg
+wMqvLMZHhHMvwLHjbvcjnnSBnvTQFn
+ttgJtRGJQctTZtZT
+CrZsJsPPZsGzwwsLwLmpwMDw
+";
+
+    #[test]
+    fn test_example() {
+        // Act
+        let duplicates = get_total_prio_of_dups(EXAMPLE);
+        let badges = get_total_prio_of_group_badges(EXAMPLE);
+
+        // Assert
+        assert_eq!(157, duplicates);
+        assert_eq!(70, badges);
+    }
+
+    #[test]
+    fn test_configurable_sizes_and_errors() {
+        // Set up
+        let input = "abcb\nbdea\n";
+
+        // Act
+        let in_quarters = calc_prio_of_duplicates(input, 4);
+        let pairs = calc_prio_of_group_badges(input, 2);
+
+        // Assert
+        assert_eq!(Ok(2), in_quarters);
+        assert_eq!(
+            Err(RucksackError::MultipleBadges {
+                group: 1,
+                badges: vec!['a', 'b']
+            }),
+            pairs
+        );
+        assert_eq!(
+            Err(RucksackError::UnevenCompartments {
+                line: 2,
+                length: 3,
+                compartments: 2
+            }),
+            calc_prio_of_duplicates("ab\nabc\n", 2)
+        );
+        assert_eq!(
+            Err(RucksackError::UnevenGroups {
+                rucksacks: 2,
+                group_size: 3
+            }),
+            calc_prio_of_group_badges(input, 3)
+        );
+    }
+
+    #[test]
+    fn test_priority_schemes() {
+        // Set up
+        let input = "1231\n4564\n";
+        let table = TablePriorities::new("ab+c").unwrap();
+
+        // Act
+        let digits = calc_prio_of_duplicates_with(input, 2, &DigitPriorities);
+        let from_table = calc_prio_of_duplicates_with("a+c+\nbccb\n", 2, &table);
+
+        // Assert
+        assert_eq!(Ok(2 + 5), digits);
+        assert_eq!(Ok(3 + 2 + 4), from_table);
+        assert_eq!(
+            Err(PriorityTableError::Duplicate('a')),
+            TablePriorities::new("aba")
+        );
+        assert_eq!(Some('Z'), LetterPriorities.item_of(52));
+    }
+}
@@ -1,115 +1,335 @@
-use core::{cmp, num::ParseIntError, str::FromStr};
+use crate::intervals::{Interval, IntervalSet};
+use crate::parsing::combinators::{
+    integer, literal, map, parse_lines, parse_whole, try_map, tuple, Parser,
+};
+use crate::parsing::{self, ParseError, StreamError};
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::io::BufRead;
 
+/// Section IDs an elf has to clean up.
+pub type Assignment = Interval<u32>;
+/// Number of pairs, wide enough for streams with more than `u32::MAX` lines.
+pub type PairCount = u64;
 type PuzzelInput = Vec<(Assignment, Assignment)>;
 
-pub fn calc_number_contained_assignment(input: &str) -> u32 {
+/// Statistics over all pairs of elves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssignmentStats {
+    pub pairs: PuzzelInput,
+    /// Number of sections both elves of a pair have, one entry per line.
+    pub overlaps: Vec<u64>,
+    /// How many pairs share how many sections.
+    pub overlap_distribution: BTreeMap<u64, usize>,
+    /// Sections claimed by the most elves and by how many of them.
+    pub most_claimed: IntervalSet<u32>,
+    pub most_claims: usize,
+}
+
+pub fn calc_number_contained_assignment(input: &str) -> PairCount {
     let parsed = parse_input(input);
 
     count_total_containments(&parsed)
 }
 
-pub fn calc_for_any_common_section(input: &str) -> u32 {
+pub fn calc_for_any_common_section(input: &str) -> PairCount {
     let parsed = parse_input(input);
 
     count_any_section_containment(&parsed)
 }
 
-fn count_total_containments(parsed_input: &PuzzelInput) -> u32 {
-    return parsed_input
-        .into_iter()
-        .fold(u32::default(), |total, pair| {
-            let (left, right) = pair;
-            let left_is_contained = get_one_or_zero(left, right);
-            let right_is_contained = get_one_or_zero(right, left);
-
-            // One line should yield still 1 even in case that two section groups overlap  each
-            // other
-            total + cmp::max(left_is_contained, right_is_contained)
-        });
-
-    fn get_one_or_zero(one: &Assignment, other: &Assignment) -> u32 {
-        if one.is_contained_fully_by(other) {
-            1u32
-        } else {
-            0u32
-        }
-    }
+/// Number of sections at least one elf has to clean up.
+pub fn calc_sections_covered(input: &str) -> u64 {
+    let parsed = parse_input(input);
+
+    count_sections(cover_sections(parsed.iter()).intervals())
+}
+
+/// Counts line by line without keeping all pairs in memory.
+pub fn calc_number_contained_assignment_from_reader(
+    reader: impl BufRead,
+) -> Result<PairCount, StreamError> {
+    count_pairs_where(reader, is_one_contained)
+}
+
+pub fn calc_for_any_common_section_from_reader(
+    reader: impl BufRead,
+) -> Result<PairCount, StreamError> {
+    count_pairs_where(reader, Assignment::overlaps)
+}
+
+/// Only keeps the merged sections in memory, not the assignments.
+pub fn calc_sections_covered_from_reader(reader: impl BufRead) -> Result<u64, StreamError> {
+    let pair = pair_parser();
+    let mut covered = IntervalSet::new();
+    parsing::for_each_line(reader, |line| {
+        let (left, right) = parse_whole(&pair, line)?;
+        covered.insert(left);
+        covered.insert(right);
+        Ok(())
+    })?;
+
+    Ok(count_sections(covered.intervals()))
+}
+
+pub fn get_assignment_stats(input: &str) -> AssignmentStats {
+    AssignmentStats::new(parse_input(input))
 }
 
-fn count_any_section_containment(parsed_input: &PuzzelInput) -> u32 {
+pub fn get_assignment_stats_from_reader(
+    reader: impl BufRead,
+) -> Result<AssignmentStats, StreamError> {
+    let pair = pair_parser();
+    let mut pairs = Vec::new();
+    parsing::for_each_line(reader, |line| {
+        pairs.push(parse_whole(&pair, line)?);
+        Ok(())
+    })?;
+
+    Ok(AssignmentStats::new(pairs))
+}
+
+/// Draws every assignment like the puzzle does, one line per elf and an empty
+/// line after every pair. Columns go from the smallest to the largest section
+/// of all pairs, sections above 9 are drawn by their last digit.
+pub fn render_pairs(pairs: &[(Assignment, Assignment)]) -> String {
+    let first_section = pairs
+        .iter()
+        .map(|(left, right)| left.start().min(right.start()))
+        .min()
+        .unwrap_or_default();
+    let last_section = pairs
+        .iter()
+        .map(|(left, right)| left.end().max(right.end()))
+        .max()
+        .unwrap_or_default();
+    let render = |assignment: &Assignment| {
+        let drawn: String = (first_section..=last_section)
+            .map(|section| {
+                if assignment.contains(section) {
+                    char::from_digit(section % 10, 10).expect("Is a single digit")
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        format!("{}  {}-{}\n", drawn, assignment.start(), assignment.end())
+    };
+
+    pairs
+        .iter()
+        .map(|(left, right)| format!("{}{}", render(left), render(right)))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn count_pairs_where(
+    reader: impl BufRead,
+    to_count: fn(&Assignment, &Assignment) -> bool,
+) -> Result<PairCount, StreamError> {
+    let pair = pair_parser();
+    let mut counted: PairCount = 0;
+    parsing::for_each_line(reader, |line| {
+        let (left, right) = parse_whole(&pair, line)?;
+        counted += PairCount::from(to_count(&left, &right));
+        Ok(())
+    })?;
+
+    Ok(counted)
+}
+
+fn count_total_containments(parsed_input: &PuzzelInput) -> PairCount {
     parsed_input
-        .into_iter()
-        .fold(u32::default(), |total, pair| {
-            let (left, right) = pair;
-            let to_add = if left.has_any_common_section(right) {
-                1u32
-            } else {
-                0u32
-            };
-
-            total + to_add
+        .iter()
+        .fold(PairCount::default(), |total, (left, right)| {
+            // One line should yield still 1 even in case that two section groups
+            // are the same.
+            total + PairCount::from(is_one_contained(left, right))
         })
 }
 
-fn parse_input(input: &str) -> PuzzelInput {
-    return input
-        .lines()
-        .map(|line| {
-            let mut left_right = line.split(",");
-            match (left_right.next(), left_right.next()) {
-                (Some(first), Some(second)) => {
-                    match (first.parse::<Assignment>(), second.parse::<Assignment>()) {
-                        (Ok(fst_parsed), Ok(snd_parsed)) => (fst_parsed, snd_parsed),
-                        _ => {
-                            panic!("For left or right elf start and end section could be extracted")
-                        }
-                    }
-                }
-                _ => panic!("no left or right elf found during parsing"),
-            }
+fn count_any_section_containment(parsed_input: &PuzzelInput) -> PairCount {
+    parsed_input
+        .iter()
+        .fold(PairCount::default(), |total, (left, right)| {
+            total + PairCount::from(left.overlaps(right))
         })
-        .collect();
 }
 
-#[derive(Debug)]
-struct Assignment {
-    start: u32,
-    end: u32,
+fn is_one_contained(left: &Assignment, right: &Assignment) -> bool {
+    left.contains_interval(right) || right.contains_interval(left)
 }
 
-impl Assignment {
-    fn new(start: u32, end: u32) -> Self {
-        Self { start, end }
-    }
+/// Counted as u64 since all sections from 0 to `u32::MAX` are one more than
+/// fits into u32.
+fn count_sections(sections: &[Assignment]) -> u64 {
+    sections
+        .iter()
+        .map(|interval| u64::from(interval.end() - interval.start()) + 1)
+        .sum()
+}
+
+fn cover_sections<'a>(
+    pairs: impl Iterator<Item = &'a (Assignment, Assignment)>,
+) -> IntervalSet<u32> {
+    pairs.flat_map(|(left, right)| [*left, *right]).collect()
+}
 
-    fn is_contained_fully_by(&self, other: &Self) -> bool {
-        self.start >= other.start && self.end <= other.end
+impl AssignmentStats {
+    fn new(pairs: PuzzelInput) -> Self {
+        let overlaps: Vec<u64> = pairs
+            .iter()
+            .map(|(left, right)| count_sections(left.intersection(right).as_slice()))
+            .collect();
+        let mut overlap_distribution = BTreeMap::new();
+        for overlap in &overlaps {
+            *overlap_distribution.entry(*overlap).or_insert(0) += 1;
+        }
+        let (most_claimed, most_claims) = find_most_claimed(&pairs);
+
+        Self {
+            pairs,
+            overlaps,
+            overlap_distribution,
+            most_claimed,
+            most_claims,
+        }
     }
+}
 
-    fn has_any_common_section(&self, other: &Self) -> bool {
-        let (smaller_start, bigger_start) = Self::sort_by_start(self, other);
+/// Sweeps over the starts and ends of all assignments while counting how
+/// many elves claim the sections in between.
+fn find_most_claimed(pairs: &PuzzelInput) -> (IntervalSet<u32>, usize) {
+    let mut changes: BTreeMap<u32, isize> = BTreeMap::new();
+    for assignment in pairs.iter().flat_map(|(left, right)| [left, right]) {
+        *changes.entry(assignment.start()).or_insert(0) += 1;
+        if let Some(after_end) = assignment.end().checked_add(1) {
+            *changes.entry(after_end).or_insert(0) -= 1;
+        }
+    }
 
-        smaller_start.end >= bigger_start.start
+    let mut claims = 0isize;
+    let mut segments = Vec::with_capacity(changes.len());
+    let mut positions = changes.iter().peekable();
+    while let Some((start, change)) = positions.next() {
+        claims += change;
+        let end = positions
+            .peek()
+            .map(|(next_start, _)| *next_start - 1)
+            .unwrap_or(u32::MAX);
+        segments.push((
+            Assignment::new(*start, end).expect("Starts are sorted"),
+            claims,
+        ));
     }
 
-    fn sort_by_start<'a>(left: &'a Self, right: &'a Self) -> (&'a Self, &'a Self) {
-        if left.start <= right.start {
-            (left, right)
-        } else {
-            (right, left)
+    let most_claims = segments
+        .iter()
+        .map(|(_, claims)| *claims)
+        .max()
+        .unwrap_or(0);
+    let most_claimed = segments
+        .into_iter()
+        .filter(|(_, claims)| most_claims > 0 && *claims == most_claims)
+        .map(|(segment, _)| segment)
+        .collect();
+
+    (most_claimed, most_claims as usize)
+}
+
+impl Display for AssignmentStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (line, overlap) in self.overlaps.iter().enumerate() {
+            writeln!(f, "Line {}: {} common sections", line + 1, overlap)?;
         }
+        for (overlap, pairs) in &self.overlap_distribution {
+            writeln!(f, "{} pairs share {} sections", pairs, overlap)?;
+        }
+        let most_claimed: Vec<String> = self
+            .most_claimed
+            .intervals()
+            .iter()
+            .map(|sections| format!("{}-{}", sections.start(), sections.end()))
+            .collect();
+        write!(
+            f,
+            "Sections claimed by the most elves ({}): {}",
+            self.most_claims,
+            most_claimed.join(", ")
+        )
     }
 }
 
-impl FromStr for Assignment {
-    type Err = ParseIntError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut start_end = s.split("-");
-        let left = start_end.next().expect("no start found");
-        let right = start_end.next().expect("no end found");
-        let start: u32 = left.parse()?;
-        let end: u32 = right.parse()?;
+fn parse_input(input: &str) -> PuzzelInput {
+    parse_lines(&pair_parser(), input).unwrap_or_else(|error| panic!("{}", error))
+}
+
+fn pair_parser() -> impl Parser<(Assignment, Assignment)> {
+    map(
+        tuple((assignment(), literal(","), assignment())),
+        |(left, _, right)| (left, right),
+    )
+}
+
+fn assignment() -> impl Parser<Assignment> {
+    try_map(
+        tuple((integer(), literal("-"), integer())),
+        |(start, _, end)| {
+            Assignment::new(start, end).map_err(|error| ParseError::new(error.to_string()))
+        },
+    )
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_example() {
+        // Set up
+        let input = "2-4,6-8\n2-3,4-5\n5-7,7-9\n2-8,3-7\n6-6,4-6\n2-6,4-8\n";
+
+        // Act
+        let contained = calc_number_contained_assignment(input);
+        let overlapping = calc_for_any_common_section_from_reader(input.as_bytes());
+        let covered = calc_sections_covered(input);
+        let all_sections = calc_sections_covered_from_reader("0-4294967295,7-9\n".as_bytes());
+
+        // Assert
+        assert_eq!(2, contained);
+        assert_eq!(Ok(4), overlapping.map_err(|error| error.to_string()));
+        assert_eq!(8, covered);
+        assert_eq!(
+            Ok(4_294_967_296),
+            all_sections.map_err(|error| error.to_string())
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        // Set up
+        let input = "2-4,6-8\n5-7,7-9\n2-6,4-8\n";
+
+        // Act
+        let actual = get_assignment_stats(input);
+        let drawn = render_pairs(&actual.pairs[..1]);
+        let from_zero = get_assignment_stats("0-2,1-1\n");
+        let full_range = get_assignment_stats("0-4294967295,0-4294967295\n");
 
-        Ok(Self::new(start, end))
+        // Assert
+        assert_eq!(vec![0, 1, 3], actual.overlaps);
+        assert_eq!(vec![4_294_967_296], full_range.overlaps);
+        assert_eq!(
+            BTreeMap::from([(0, 1), (1, 1), (3, 1)]),
+            actual.overlap_distribution
+        );
+        assert_eq!(4, actual.most_claims);
+        assert_eq!(
+            &[Assignment::new(6, 7).unwrap()],
+            actual.most_claimed.intervals()
+        );
+        assert_eq!("234....  2-4\n....678  6-8\n", drawn);
+        assert_eq!("012  0-2\n.1.  1-1\n", render_pairs(&from_zero.pairs));
     }
 }
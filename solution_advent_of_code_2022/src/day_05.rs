@@ -1,6 +1,7 @@
 use crate::parsing;
 use core::str::FromStr;
 use std::collections::VecDeque;
+use std::sync::OnceLock;
 type CrateCells = Vec<Option<char>>;
 type CrateToFillIn = Vec<CrateCells>;
 
@@ -42,42 +43,32 @@ impl Instruction {
     }
 }
 
+static INSTRUCTION_PARSER: OnceLock<parsing::CaptureParser> = OnceLock::new();
+
 impl FromStr for Instruction {
-    type Err = ();
+    type Err = parsing::ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let seq = parsing::get_seq_from_regex(r"move (\d+) from (\d+) to (\d+)", s, 3)
-            .expect("Could not parse instruction");
-
-        let mut numbers = seq
-            .into_iter()
-            .map(|to_parse| to_parse.parse::<u32>().expect("Could not parse to number"));
-
-        match (numbers.next(), numbers.next(), numbers.next()) {
-            (Some(movement), Some(start), Some(dest)) => Ok(Self {
-                movement,
-                start,
-                dest,
-            }),
-            _ => panic!("Could not parse instruction"),
-        }
+        let (movement, start, dest) = INSTRUCTION_PARSER
+            .get_or_init(|| {
+                parsing::CaptureParser::new(r"move (\d+) from (\d+) to (\d+)")
+                    .expect("Pattern for instruction is valid")
+            })
+            .parse(s)?;
+
+        Ok(Self {
+            movement,
+            start,
+            dest,
+        })
     }
 }
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct CraneInProgress {
     crate_stacks: Vec<Vec<char>>,
     to_do: Vec<Instruction>,
 }
 
-impl Default for CraneInProgress {
-    fn default() -> Self {
-        Self {
-            crate_stacks: Vec::new(),
-            to_do: Vec::new(),
-        }
-    }
-}
-
 impl CraneInProgress {
     fn init(&mut self, number_of_stacks: usize) {
         for _ in 0..number_of_stacks {
@@ -125,7 +116,7 @@ impl CraneInProgress {
                 to_move.push_front(*popped);
             }
 
-            self.crate_stacks[next_to_do.get_dest_index()].extend(to_move.into_iter());
+            self.crate_stacks[next_to_do.get_dest_index()].extend(to_move);
         }
     }
 }
@@ -136,7 +127,7 @@ fn parse_input(input: &str) -> CraneInProgress {
     let lines: Vec<&str> = input.lines().collect();
     let to_bottom_crates = lines
         .iter()
-        .position(|line| line.chars().any(|c| c.is_digit(10)))
+        .position(|line| line.chars().any(|c| c.is_ascii_digit()))
         .expect("no stack number line found");
 
     let start_index_inst = to_bottom_crates + OFFSET_STACK_NUM_INST;
@@ -144,9 +135,14 @@ fn parse_input(input: &str) -> CraneInProgress {
 
     crane.to_do = lines
         .into_iter()
+        .enumerate()
         .skip(start_index_inst)
-        .map(|line| line.parse().unwrap())
-        .collect();
+        .map(|(index, line)| {
+            line.parse()
+                .map_err(|error: parsing::ParseError| error.at_line(index + 1))
+        })
+        .collect::<Result<Vec<Instruction>, _>>()
+        .unwrap_or_else(|error| panic!("{}", error));
 
     crane
 }
@@ -1,24 +1,376 @@
-use std::collections::HashSet;
-pub fn get_end_of_first_packet_start(input: &str, size_marker: usize) -> u32 {
-    let chars: Vec<char> = input.chars().collect();
-    let limit = chars.len() - (size_marker - 1);
-    let mut symb_cache: HashSet<char> = Default::default();
-    for (index, current_symb) in (&chars[0..limit]).iter().enumerate() {
-        symb_cache.clear();
-        symb_cache.insert(*current_symb);
-        let sub_limit = index + size_marker;
-        let mut found_no_dup = true;
-        for next in (&chars[(index + 1)..(sub_limit)]).iter() {
-            if !symb_cache.insert(*next) {
-                found_no_dup = false;
-                break;
+use std::collections::VecDeque;
+use std::fmt::Display;
+use std::io::{self, BufRead};
+
+/// Symbols of a datastream are expected to be ASCII, every byte counts as one
+/// symbol.
+const NUMBER_OF_SYMBOLS: usize = 256;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MarkerError {
+    ZeroSize,
+    TooShort { length: usize, size_marker: usize },
+    NotFound,
+    Io(io::ErrorKind),
+}
+
+/// Sizes of the markers which frame a datastream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameConfig {
+    /// Marker before the first frame, everything before it is noise.
+    pub start_marker_size: usize,
+    /// Marker between two frames, its symbols belong to no frame.
+    pub delimiter_marker_size: usize,
+}
+
+/// Payload between two markers and the position of its first symbol.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub offset: usize,
+    pub payload: Vec<u8>,
+}
+
+/// Iterator over the frames of a datastream, see `decode_frames`.
+pub struct Frames<I> {
+    symbols: I,
+    config: FrameConfig,
+    window: MarkerWindow,
+    started: bool,
+    finished: bool,
+    position: usize,
+    offset: usize,
+    payload: Vec<u8>,
+}
+
+/// Last symbols of a datastream with how often each of them occurs, so
+/// moving the window by one symbol costs constant time.
+struct MarkerWindow {
+    size_marker: usize,
+    symbols: VecDeque<u8>,
+    counts: [usize; NUMBER_OF_SYMBOLS],
+    duplicates: usize,
+}
+
+/// Position right after the first marker like the puzzle counts it.
+pub fn get_end_of_first_packet_start(
+    input: &str,
+    size_marker: usize,
+) -> Result<usize, MarkerError> {
+    let datastream = input.trim_end();
+    get_all_marker_ends(datastream, size_marker)?
+        .next()
+        .ok_or(if datastream.len() < size_marker {
+            MarkerError::TooShort {
+                length: datastream.len(),
+                size_marker,
+            }
+        } else {
+            MarkerError::NotFound
+        })
+}
+
+/// Ends of all windows whose symbols are all different. Windows overlap, so
+/// "abcd" followed by "e" yields the positions 4 and 5 for markers of size 4.
+pub fn get_all_marker_ends(
+    input: &str,
+    size_marker: usize,
+) -> Result<impl Iterator<Item = usize> + '_, MarkerError> {
+    let mut window = MarkerWindow::new(size_marker)?;
+
+    Ok(input
+        .trim_end()
+        .bytes()
+        .enumerate()
+        .filter_map(move |(index, symbol)| window.push(symbol).then_some(index + 1)))
+}
+
+/// Same as `get_end_of_first_packet_start` but only keeps the last
+/// `size_marker` symbols in memory. Reading stops at the end of the first line.
+pub fn get_end_of_first_packet_start_from_reader(
+    reader: impl BufRead,
+    size_marker: usize,
+) -> Result<usize, MarkerError> {
+    // Before a marker could be found, the line either ends too short or
+    // fails to be read, so the first item decides.
+    get_all_marker_ends_from_reader(reader, size_marker)?
+        .next()
+        .unwrap_or(Err(MarkerError::NotFound))
+}
+
+/// Ends of all markers in the first line of the reader. The last item is an
+/// error with the number of read symbols if the line is shorter than a marker.
+pub fn get_all_marker_ends_from_reader(
+    reader: impl BufRead,
+    size_marker: usize,
+) -> Result<impl Iterator<Item = Result<usize, MarkerError>>, MarkerError> {
+    let mut window = MarkerWindow::new(size_marker)?;
+    let mut symbols = read_first_line(reader);
+    let mut read = 0;
+    let mut finished = false;
+
+    Ok(std::iter::from_fn(move || {
+        while !finished {
+            match symbols.next() {
+                Some(Ok(symbol)) => {
+                    read += 1;
+                    if window.push(symbol) {
+                        return Some(Ok(read));
+                    }
+                }
+                Some(Err(error)) => {
+                    finished = true;
+                    return Some(Err(error));
+                }
+                None => {
+                    finished = true;
+                    if read < size_marker {
+                        return Some(Err(MarkerError::TooShort {
+                            length: read,
+                            size_marker,
+                        }));
+                    }
+                }
             }
         }
 
-        if found_no_dup {
-            return sub_limit as u32;
+        None
+    }))
+}
+
+/// Splits a datastream into frames. Frames start after the start marker and
+/// are delimited by the following markers, whose search starts fresh after
+/// every marker. The rest after the last marker is the last frame.
+pub fn decode_frames(
+    input: &str,
+    config: FrameConfig,
+) -> Result<Frames<impl Iterator<Item = Result<u8, MarkerError>> + '_>, MarkerError> {
+    Frames::new(input.trim_end().bytes().map(Ok), config)
+}
+
+/// Same as `decode_frames` for the first line of a reader. Only the current
+/// frame is kept in memory.
+pub fn decode_frames_from_reader(
+    reader: impl BufRead,
+    config: FrameConfig,
+) -> Result<Frames<impl Iterator<Item = Result<u8, MarkerError>>>, MarkerError> {
+    Frames::new(read_first_line(reader), config)
+}
+
+fn read_first_line(reader: impl BufRead) -> impl Iterator<Item = Result<u8, MarkerError>> {
+    reader
+        .bytes()
+        .take_while(|symbol| !matches!(symbol, Ok(b'\n')))
+        .filter(|symbol| !matches!(symbol, Ok(b'\r')))
+        .map(|symbol| symbol.map_err(|error| MarkerError::Io(error.kind())))
+}
+
+impl Default for FrameConfig {
+    fn default() -> Self {
+        Self {
+            start_marker_size: 4,
+            delimiter_marker_size: 14,
         }
     }
+}
+
+impl<I: Iterator<Item = Result<u8, MarkerError>>> Frames<I> {
+    fn new(symbols: I, config: FrameConfig) -> Result<Self, MarkerError> {
+        MarkerWindow::new(config.delimiter_marker_size)?;
+
+        Ok(Self {
+            symbols,
+            config,
+            window: MarkerWindow::new(config.start_marker_size)?,
+            started: false,
+            finished: false,
+            position: 0,
+            offset: 0,
+            payload: Vec::new(),
+        })
+    }
+
+    fn fresh_window(&self) -> MarkerWindow {
+        MarkerWindow::new(self.config.delimiter_marker_size).expect("Size is checked in new")
+    }
+}
+
+impl<I: Iterator<Item = Result<u8, MarkerError>>> Iterator for Frames<I> {
+    type Item = Result<Frame, MarkerError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.finished {
+            let symbol = match self.symbols.next() {
+                Some(Ok(symbol)) => symbol,
+                Some(Err(error)) => {
+                    self.finished = true;
+                    return Some(Err(error));
+                }
+                None => {
+                    self.finished = true;
+                    return if !self.started {
+                        Some(Err(MarkerError::NotFound))
+                    } else if self.payload.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(Frame {
+                            offset: self.offset,
+                            payload: std::mem::take(&mut self.payload),
+                        }))
+                    };
+                }
+            };
+            self.position += 1;
 
-    panic!("No start for start packet found");
+            if !self.started {
+                if self.window.push(symbol) {
+                    self.started = true;
+                    self.window = self.fresh_window();
+                    self.offset = self.position;
+                }
+                continue;
+            }
+
+            self.payload.push(symbol);
+            if self.window.push(symbol) {
+                let marker_start = self.payload.len() - self.config.delimiter_marker_size;
+                self.payload.truncate(marker_start);
+                self.window = self.fresh_window();
+
+                let frame = Frame {
+                    offset: self.offset,
+                    payload: std::mem::take(&mut self.payload),
+                };
+                self.offset = self.position;
+                return Some(Ok(frame));
+            }
+        }
+
+        None
+    }
+}
+
+impl MarkerWindow {
+    fn new(size_marker: usize) -> Result<Self, MarkerError> {
+        if size_marker == 0 {
+            return Err(MarkerError::ZeroSize);
+        }
+
+        Ok(Self {
+            size_marker,
+            symbols: VecDeque::with_capacity(size_marker),
+            counts: [0; NUMBER_OF_SYMBOLS],
+            duplicates: 0,
+        })
+    }
+
+    /// True if the window is full and has no symbol twice after the push.
+    fn push(&mut self, symbol: u8) -> bool {
+        if self.symbols.len() == self.size_marker {
+            let dropped = self.symbols.pop_front().expect("Window is full") as usize;
+            self.counts[dropped] -= 1;
+            if self.counts[dropped] > 0 {
+                self.duplicates -= 1;
+            }
+        }
+
+        self.symbols.push_back(symbol);
+        self.counts[symbol as usize] += 1;
+        if self.counts[symbol as usize] > 1 {
+            self.duplicates += 1;
+        }
+
+        self.symbols.len() == self.size_marker && self.duplicates == 0
+    }
+}
+
+impl Display for MarkerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MarkerError::ZeroSize => write!(f, "A marker needs at least one symbol"),
+            MarkerError::TooShort {
+                length,
+                size_marker,
+            } => write!(
+                f,
+                "Datastream with {} symbols is shorter than a marker of {}",
+                length, size_marker
+            ),
+            MarkerError::NotFound => write!(f, "No marker found in the datastream"),
+            MarkerError::Io(kind) => write!(f, "Could not read datastream: {}", kind),
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_first_marker() {
+        // Set up
+        let input = "mjqjpqmgbljsphdztnvjfqwrcgsmlb\n";
+
+        // Act
+        let packet = get_end_of_first_packet_start(input, 4);
+        let message = get_end_of_first_packet_start_from_reader(input.as_bytes(), 14);
+
+        // Assert
+        assert_eq!(Ok(7), packet);
+        assert_eq!(Ok(19), message);
+        assert_eq!(
+            Err(MarkerError::TooShort {
+                length: 3,
+                size_marker: 4
+            }),
+            get_end_of_first_packet_start("abc", 4)
+        );
+        assert_eq!(
+            Err(MarkerError::NotFound),
+            get_end_of_first_packet_start_from_reader("aaaa".as_bytes(), 2)
+        );
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        // Set up
+        let config = FrameConfig {
+            start_marker_size: 3,
+            delimiter_marker_size: 3,
+        };
+
+        // Act
+        let actual: Result<Vec<Frame>, MarkerError> =
+            decode_frames("xxabcaabbxyzbbccxyzdd", config)
+                .unwrap()
+                .collect();
+
+        // Assert
+        assert_eq!(
+            Ok(vec![
+                Frame {
+                    offset: 4,
+                    payload: b"caab".to_vec()
+                },
+                Frame {
+                    offset: 11,
+                    payload: b"zbbc".to_vec()
+                },
+                Frame {
+                    offset: 18,
+                    payload: b"zdd".to_vec()
+                },
+            ]),
+            actual
+        );
+    }
+
+    #[test]
+    fn test_all_marker_ends() {
+        // Act
+        let actual: Vec<usize> = get_all_marker_ends("abcaab", 3).unwrap().collect();
+
+        // Assert
+        assert_eq!(vec![3, 4], actual);
+    }
 }
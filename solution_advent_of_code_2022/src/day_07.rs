@@ -80,11 +80,11 @@ pub fn get_directory_to_delete(
     return get_smallest_possible_dir(&size_journal, needed_additional_free);
 
     fn get_smallest_possible_dir(
-        size_journal: &Vec<(String, u128)>,
+        size_journal: &[(String, u128)],
         needed_additional_free: SizeOfFile,
     ) -> (String, SizeOfFile, SizeOfFile) {
         let smallest_enough_dir = size_journal
-            .into_iter()
+            .iter()
             .filter(|entry| {
                 let (_, size) = entry;
 
@@ -240,7 +240,7 @@ fn parse_commands(to_convert: Vec<&str>) -> Vec<ChangeDirectory> {
         .collect()
 }
 
-fn section_commands_and_entries(input: &str) -> ChangeAndLsPair {
+fn section_commands_and_entries(input: &str) -> ChangeAndLsPair<'_> {
     let mut extracted: ChangeAndLsPair = Vec::new();
     let mut commands: Vec<&str> = Default::default();
     let mut entries: Vec<&str> = Default::default();
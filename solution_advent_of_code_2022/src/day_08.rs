@@ -1,4 +1,3 @@
-use core::iter::repeat;
 use core::num::IntErrorKind;
 
 use crate::sequences;
@@ -48,14 +47,10 @@ fn fill_grid_score_brute_force(grid: &TreeGrid, scores: &mut TreeSenicScores) {
     let height = grid.len();
     let width = grid[0].len();
     // zero out first row
-    for x in 0..width {
-        scores[0][x] = 0;
-    }
+    scores[0][..width].fill(0);
     // zero out last row
     let last_row = scores.last_mut().unwrap();
-    for x in 0..width {
-        last_row[x] = 0;
-    }
+    last_row[..width].fill(0);
 
     for y in 1..(height - 1) {
         *scores[y].first_mut().unwrap() = 0;
@@ -149,20 +144,21 @@ fn inpect_grid_for_visible(grid: &TreeGrid) -> TreeVisibility {
 }
 
 fn get_iter_rows(row_index: usize, width: usize) -> Vec<(usize, usize)> {
-    repeat(row_index)
-        .take(width)
+    std::iter::repeat_n(row_index, width)
         .enumerate()
         .map(|x_y| (x_y.1, x_y.0))
         .collect()
 }
 
 fn get_iter_column(column_index: usize, height: usize) -> Vec<(usize, usize)> {
-    repeat(column_index).take(height).enumerate().collect()
+    std::iter::repeat_n(column_index, height)
+        .enumerate()
+        .collect()
 }
 
 fn inspect_sequence_for_visble(
-    grid: &Vec<Vec<Digit>>,
-    visible: &mut Vec<Vec<bool>>,
+    grid: &[Vec<Digit>],
+    visible: &mut [Vec<bool>],
     mut sequence: Vec<(usize, usize)>,
 ) {
     // left
@@ -171,7 +167,7 @@ fn inspect_sequence_for_visble(
     for (y, x) in sequence.iter() {
         let current_digit = grid[*y][*x];
         if current_digit > last_max {
-            last_max = current_digit.clone();
+            last_max = current_digit;
             visible[*y][*x] = true;
         }
     }
@@ -183,14 +179,14 @@ fn inspect_sequence_for_visble(
     for (y, x) in sequence {
         let current_digit = grid[y][x];
         if current_digit > last_max {
-            last_max = current_digit.clone();
+            last_max = current_digit;
             visible[y][x] = true;
         }
     }
 }
 
 #[allow(dead_code)]
-fn get_view_no_edges<'a>(with_edges: &'a TreeGrid) -> TreeGrid {
+fn get_view_no_edges(with_edges: &TreeGrid) -> TreeGrid {
     let to_take = with_edges.len() - 2usize;
     with_edges
         .iter()
@@ -1,5 +1,5 @@
+use crate::parsing::combinators::{alt, integer, literal, map, parse_lines, spaces, tuple, value};
 use std::collections::HashSet;
-use std::iter::repeat;
 type HeadSteps = Vec<HeadMovement>;
 
 #[derive(Default, Debug, Clone, Hash, PartialEq, Eq)]
@@ -36,7 +36,7 @@ pub fn get_visted_number_of_last_tail(input: &str, number: usize) -> usize {
 }
 
 fn get_nth_number_visited(input: &HeadSteps, number: usize) -> usize {
-    let mut tails: Vec<Tail> = repeat(Tail::default()).take(number).collect();
+    let mut tails: Vec<Tail> = std::iter::repeat_n(Tail::default(), number).collect();
     let mut head: Head = Default::default();
 
     let mut found: HashSet<Coord> = Default::default();
@@ -76,7 +76,7 @@ fn get_nth_number_visited(input: &HeadSteps, number: usize) -> usize {
 
     fn return_if_last_resolved(
         head: &Head,
-        tails: &mut Vec<Tail>,
+        tails: &mut [Tail],
         found: &mut HashSet<Coord>,
     ) -> usize {
         if let Some(crood) = resolve_movement_head(head, tails) {
@@ -90,7 +90,7 @@ fn get_nth_number_visited(input: &HeadSteps, number: usize) -> usize {
         0
     }
 
-    fn resolve_movement_head(head: &Head, tails: &mut Vec<Tail>) -> Option<Coord> {
+    fn resolve_movement_head(head: &Head, tails: &mut [Tail]) -> Option<Coord> {
         let mut current_element = head.0.clone();
         let mut last_resolved = None;
 
@@ -143,24 +143,18 @@ fn simulation_head_tail(input: &HeadSteps) -> (SimulationResult, usize) {
         }
 
         fn return_inc_if_resolved(sim: &mut SimulationResult, found: &mut HashSet<Coord>) -> usize {
-            match resolve_if_needed(&mut sim.tail.0, &mut sim.head.0) {
-                Some(coord) => {
-                    if found.insert(coord) {
-                        1
-                    } else {
-                        0
-                    }
-                }
+            match resolve_if_needed(&mut sim.tail.0, &sim.head.0) {
+                Some(coord) => usize::from(found.insert(coord)),
                 None => 0,
             }
         }
     }
 
-    return (simulation, moved_fiels);
+    (simulation, moved_fiels)
 }
 
 fn resolve_if_needed(tail: &mut Coord, head: &Coord) -> Option<Coord> {
-    if let Some(to_resolve) = tail.get_distances_if_needed(&head) {
+    if let Some(to_resolve) = tail.get_distances_if_needed(head) {
         let (x_dist, y_dist) = to_resolve;
         let x_dist_abs = x_dist.abs();
         let y_dist_abs = y_dist.abs();
@@ -187,29 +181,19 @@ fn resolve_if_needed(tail: &mut Coord, head: &Coord) -> Option<Coord> {
 }
 
 fn parse_input(input: &str) -> HeadSteps {
-    input
-        .lines()
-        .map(|line| {
-            let mut splits = line.split(" ");
-
-            match (splits.next(), splits.next()) {
-                (Some(direction), Some(steps)) => {
-                    let steps_parsed: u32 = steps
-                        .parse()
-                        .expect("Right part from whitespace is not valid unsigned number");
-
-                    match direction {
-                        "U" => HeadMovement::Up(steps_parsed),
-                        "D" => HeadMovement::Down(steps_parsed),
-                        "L" => HeadMovement::Left(steps_parsed),
-                        "R" => HeadMovement::Right(steps_parsed),
-                        _ => panic!("{direction} is not parseable for a direction"),
-                    }
-                }
-                _ => panic!("Line being parsed has no left and right part between space"),
-            }
-        })
-        .collect()
+    type ToMovement = fn(u32) -> HeadMovement;
+    let direction = alt((
+        value(literal("U"), HeadMovement::Up as ToMovement),
+        value(literal("D"), HeadMovement::Down as ToMovement),
+        value(literal("L"), HeadMovement::Left as ToMovement),
+        value(literal("R"), HeadMovement::Right as ToMovement),
+    ));
+    let movement = map(
+        tuple((direction, spaces(), integer())),
+        |(to_movement, _, steps)| to_movement(steps),
+    );
+
+    parse_lines(&movement, input).unwrap_or_else(|error| panic!("{}", error))
 }
 impl Coord {
     fn go_top(&mut self) {
@@ -1,16 +1,168 @@
-type CpuProgram = Vec<CpuInst>;
-use core::fmt::Debug;
-#[derive(Debug)]
-enum CpuInst {
-    Noop,
-    Addx(i64),
+use crate::advent_font::{self, OcrError};
+use crate::parsing::combinators::{integer, parse_whole, separated, spaces, token, trimmed};
+use core::fmt::{Debug, Display};
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+
+pub type RegisterName = char;
+pub type RegisterValue = i64;
+pub type CpuProgram = Vec<CpuInst>;
+/// Rows of pixels of the crt, true for a lit pixel.
+pub type CrtPixels = Vec<Vec<bool>>;
+/// Applied once at the end of the last cycle of an instruction.
+pub type InstEffect = fn(&mut Registers, &[Operand]) -> Result<ProgramFlow, RegisterError>;
+
+pub const REGISTER_X: RegisterName = 'X';
+pub const REGISTER_Y: RegisterName = 'Y';
+
+pub const LIT_PIXEL: char = '#';
+pub const DARK_PIXEL: char = '.';
+
+const PBM_MAX_LINE: usize = 70;
+const PGM_MAX_GRAY: u8 = 255;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand {
+    Register(RegisterName),
+    Value(RegisterValue),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OperandKind {
+    Register,
+    Value,
+    RegisterOrValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgramFlow {
+    Next,
+    JumpBy(RegisterValue),
 }
 
-struct Cpu {
-    register: i64,
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProgramParseError {
+    UnknownInstruction {
+        line: usize,
+        name: String,
+    },
+    WrongOperandNumber {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidValue {
+        line: usize,
+        operand: String,
+    },
+    UnknownRegister {
+        line: usize,
+        operand: String,
+    },
+    Malformed {
+        line: usize,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterError {
+    Missing(RegisterName),
+    Overflow,
+}
+
+/// An instruction or an observer failed in the given cycle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CpuError {
+    pub cycle: usize,
+    pub error: RegisterError,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ProgramError {
+    Parse(ProgramParseError),
+    Runtime(CpuError),
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Registers(BTreeMap<RegisterName, RegisterValue>);
+
+#[derive(Debug, Clone)]
+struct InstDefinition {
+    operands: Vec<OperandKind>,
+    cycles: usize,
+    effect: InstEffect,
+}
+
+/// Table of known instructions and registers. A program is parsed against
+/// it, so variants of the puzzle only need another table, not another cpu.
+#[derive(Debug, Clone, Default)]
+pub struct InstructionSet {
+    definitions: HashMap<String, InstDefinition>,
+    registers: Registers,
+}
+
+#[derive(Debug, Clone)]
+pub struct CpuInst {
+    name: String,
+    operands: Vec<Operand>,
+    cycles: usize,
+    effect: InstEffect,
+}
+
+/// What happened in one cycle: registers as seen during the cycle and after
+/// it completed. The instruction is `None` once the program has terminated.
+#[derive(Debug, Clone)]
+pub struct CycleTrace {
+    pub cycle: usize,
+    pub during: Registers,
+    pub after: Registers,
+    pub instruction: Option<CpuInst>,
+}
+
+pub trait CycleObserver {
+    fn on_cycle(&mut self, trace: &CycleTrace) -> Result<(), RegisterError>;
+}
+
+struct SignalStrength {
+    cycle_steps: usize,
+    cycle_offset: usize,
+    total: i64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CrtConfig {
+    pub height: usize,
+    pub width: usize,
+    pub sprite_width: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AssembleError {
+    WrongSize { height: usize, width: usize },
+    NotDrawable { x: usize, y: usize },
+}
+
+// Cheapest way found so far to have a certain value in register X during
+// a cycle, with a flag if the value was changed right before the cycle.
+#[derive(Debug, Clone, Copy)]
+struct Reached {
+    changes: usize,
+    previous: usize,
+    previous_changed: bool,
+}
+
+struct CrtScreen {
+    config: CrtConfig,
+    pixels: CrtPixels,
+}
+
+pub struct Cpu {
+    registers: Registers,
     program_counter: usize,
     program: CpuProgram,
-    inst_countdown: usize,
+    cycles_in_inst: usize,
+    cycle: usize,
     terminated: bool,
 }
 
@@ -20,90 +172,847 @@ pub fn get_signal_strength_up_to(
     cycle_steps: usize,
     cycle_offset: usize,
 ) -> i64 {
-    let program = parse_input(input);
-    process_program(program, up_to_cycle, cycle_steps, cycle_offset)
+    get_signal_strength_with(
+        input,
+        &InstructionSet::extended(),
+        up_to_cycle,
+        cycle_steps,
+        cycle_offset,
+    )
+    .expect("Could not run program")
+}
+
+pub fn get_signal_strength_with(
+    input: &str,
+    instruction_set: &InstructionSet,
+    up_to_cycle: usize,
+    cycle_steps: usize,
+    cycle_offset: usize,
+) -> Result<i64, ProgramError> {
+    let program = instruction_set.parse_program(input)?;
+    let cpu = Cpu::new(program, instruction_set.get_initial_registers());
+
+    Ok(process_program(
+        cpu,
+        up_to_cycle,
+        cycle_steps,
+        cycle_offset,
+    )?)
 }
 
 pub fn get_drawing(input: &str, height: usize, width: usize) -> String {
-    let program = parse_input(input);
-    draw_according_to_program(program, height, width)
+    get_drawing_with(input, &InstructionSet::extended(), height, width)
+        .expect("Could not run program")
+}
+
+pub fn get_drawing_with(
+    input: &str,
+    instruction_set: &InstructionSet,
+    height: usize,
+    width: usize,
+) -> Result<String, ProgramError> {
+    let config = CrtConfig {
+        height,
+        width,
+        ..Default::default()
+    };
+    let pixels = get_pixels_with(input, instruction_set, &config)?;
+
+    Ok(render_text(&pixels, LIT_PIXEL, DARK_PIXEL))
+}
+
+pub fn get_pixels(input: &str, config: &CrtConfig) -> CrtPixels {
+    get_pixels_with(input, &InstructionSet::extended(), config).expect("Could not run program")
+}
+
+pub fn get_pixels_with(
+    input: &str,
+    instruction_set: &InstructionSet,
+    config: &CrtConfig,
+) -> Result<CrtPixels, ProgramError> {
+    let program = instruction_set.parse_program(input)?;
+    let cpu = Cpu::new(program, instruction_set.get_initial_registers());
+
+    Ok(draw_according_to_program(cpu, config)?)
+}
+
+pub fn read_letters(pixels: &[Vec<bool>]) -> Result<String, OcrError> {
+    advent_font::read_letters_from_pixels(pixels)
+}
+
+pub fn render_text(pixels: &[Vec<bool>], lit: char, dark: char) -> String {
+    let mut rendered = String::new();
+    for row in pixels {
+        rendered.extend(row.iter().map(|is_lit| if *is_lit { lit } else { dark }));
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+/// Two rows of pixels per line with unicode half blocks, which keeps the
+/// pixels roughly square in a terminal.
+pub fn render_blocks(pixels: &[Vec<bool>]) -> String {
+    let mut rendered = String::new();
+    for upper_lower in pixels.chunks(2) {
+        let upper = &upper_lower[0];
+        let lower = upper_lower.get(1);
+
+        for (x, upper_lit) in upper.iter().enumerate() {
+            let lower_lit = lower.and_then(|row| row.get(x)).copied().unwrap_or(false);
+            rendered.push(match (*upper_lit, lower_lit) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+
+        rendered.push('\n');
+    }
+
+    rendered
+}
+
+/// Plain portable bitmap, a lit pixel is drawn black.
+pub fn render_pbm(pixels: &[Vec<bool>]) -> String {
+    let values: Vec<String> = pixels
+        .iter()
+        .flatten()
+        .map(|is_lit| if *is_lit { "1" } else { "0" }.to_string())
+        .collect();
+
+    format!(
+        "P1\n{} {}\n{}",
+        get_width(pixels),
+        pixels.len(),
+        join_wrapped(&values)
+    )
+}
+
+/// Plain portable graymap, a lit pixel is drawn white like on the crt.
+pub fn render_pgm(pixels: &[Vec<bool>]) -> String {
+    let values: Vec<String> = pixels
+        .iter()
+        .flatten()
+        .map(|is_lit| if *is_lit { PGM_MAX_GRAY } else { 0 }.to_string())
+        .collect();
+
+    format!(
+        "P2\n{} {}\n{}\n{}",
+        get_width(pixels),
+        pixels.len(),
+        PGM_MAX_GRAY,
+        join_wrapped(&values)
+    )
+}
+
+fn get_width(pixels: &[Vec<bool>]) -> usize {
+    pixels.first().map(|row| row.len()).unwrap_or_default()
+}
+
+// Lines of plain netpbm files should not be longer than 70 characters.
+fn join_wrapped(values: &[String]) -> String {
+    let mut joined = String::new();
+    let mut line_length = 0;
+
+    for value in values {
+        if line_length != 0 && line_length + 1 + value.len() > PBM_MAX_LINE {
+            joined.push('\n');
+            line_length = 0;
+        } else if line_length != 0 {
+            joined.push(' ');
+            line_length += 1;
+        }
+
+        joined.push_str(value);
+        line_length += value.len();
+    }
+
+    joined.push('\n');
+    joined
+}
+
+/// One line per cycle until termination or the given number of cycles. A
+/// program which jumps back forever would never terminate otherwise.
+pub fn get_trace(input: &str, max_cycles: usize) -> String {
+    get_trace_with(input, &InstructionSet::extended(), max_cycles).expect("Could not run program")
+}
+
+pub fn get_trace_with(
+    input: &str,
+    instruction_set: &InstructionSet,
+    max_cycles: usize,
+) -> Result<String, ProgramError> {
+    let program = instruction_set.parse_program(input)?;
+    let mut cpu = Cpu::new(program, instruction_set.get_initial_registers());
+    let mut traced = cpu
+        .trace()
+        .take(max_cycles)
+        .map(|cycle| cycle.map(|traced| traced.to_string()))
+        .collect::<Result<Vec<String>, CpuError>>()?;
+
+    if !cpu.is_terminated() {
+        traced.push(not_terminated_note(max_cycles));
+    }
+
+    Ok(traced.join("\n"))
+}
+
+pub fn parse_pixels(input: &str, lit: char) -> CrtPixels {
+    input
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.chars().map(|pixel| pixel == lit).collect())
+        .collect()
 }
 
-fn draw_according_to_program(program: CpuProgram, height: usize, width: usize) -> String {
-    let pixels = height * width;
-    let mut cpu = Cpu::new(program);
-    let mut screen = String::with_capacity(pixels);
+/// Creates a program of noop and addx which draws the given pixels when
+/// run on a crt with the given config. Uses as few addx as possible.
+pub fn assemble_drawing(pixels: &[Vec<bool>], config: &CrtConfig) -> Result<String, AssembleError> {
+    let has_right_size =
+        pixels.len() == config.height && pixels.iter().all(|row| row.len() == config.width);
+    if !has_right_size {
+        return Err(AssembleError::WrongSize {
+            height: config.height,
+            width: config.width,
+        });
+    }
+
+    let wanted: Vec<bool> = pixels.iter().flatten().copied().collect();
+    if wanted.is_empty() {
+        return Ok(String::new());
+    }
 
-    for _ in 0..height {
-        for x in 0..width {
-            cpu.next_cycle();
+    let values_x = find_values_of_x(&wanted, config)?;
 
-            let current_x = cpu.get_reg_v();
-            let min_x = (current_x - 1) as usize;
-            let max_x = (current_x + 1) as usize;
+    Ok(write_program_for(&values_x))
+}
+
+/// Lists every executed instruction with the cycles it took and the
+/// registers after it, until termination or the given number of cycles.
+pub fn annotate_program(input: &str, max_cycles: usize) -> String {
+    annotate_program_with(input, &InstructionSet::extended(), max_cycles)
+        .expect("Could not run program")
+}
+
+pub fn annotate_program_with(
+    input: &str,
+    instruction_set: &InstructionSet,
+    max_cycles: usize,
+) -> Result<String, ProgramError> {
+    let program = instruction_set.parse_program(input)?;
+    let mut cpu = Cpu::new(program, instruction_set.get_initial_registers());
+    let mut annotated: Vec<String> = Vec::new();
+
+    while !cpu.is_terminated() && cpu.get_cycle() < max_cycles {
+        let start = cpu.get_cycle() + 1;
+        let line = cpu.program_counter + 1;
+        let instruction = cpu.program[cpu.program_counter].to_string();
+
+        cpu.next_cycle()?;
+        while cpu.cycles_in_inst != 0 && cpu.get_cycle() < max_cycles {
+            cpu.next_cycle()?;
+        }
+
+        let end = cpu.get_cycle();
+        let cycles = if start == end {
+            start.to_string()
+        } else {
+            format!("{start}-{end}")
+        };
 
-            if x >= min_x && x <= max_x {
-                screen.push('#');
-            } else {
-                screen.push('.');
+        annotated.push(format!(
+            "cycle {:<9} | {:>4} | {:<12} | {}",
+            cycles,
+            line,
+            instruction,
+            cpu.get_registers()
+        ));
+    }
+
+    if !cpu.is_terminated() {
+        annotated.push(not_terminated_note(max_cycles));
+    }
+
+    Ok(annotated.join("\n"))
+}
+
+fn not_terminated_note(max_cycles: usize) -> String {
+    format!(
+        "Stopped after {} cycles, the program did not terminate",
+        max_cycles
+    )
+}
+
+// Value of X can only change after an addx which takes 2 cycles.
+// So X can not change twice in a row and not before the second cycle.
+fn find_values_of_x(
+    wanted: &[bool],
+    config: &CrtConfig,
+) -> Result<Vec<RegisterValue>, AssembleError> {
+    let sprite_width = config.sprite_width as RegisterValue;
+    let candidates: Vec<RegisterValue> =
+        (-sprite_width..=(config.width as RegisterValue + sprite_width)).collect();
+    let initial = InstructionSet::puzzle()
+        .get_initial_registers()
+        .get(REGISTER_X)
+        .expect("Puzzle has register X");
+    let fits = |cycle_index: usize, value: RegisterValue| {
+        config.is_covered_by_sprite(cycle_index % config.width, value) == wanted[cycle_index]
+    };
+    let not_drawable = |cycle_index: usize| AssembleError::NotDrawable {
+        x: cycle_index % config.width,
+        y: cycle_index / config.width,
+    };
+
+    let mut table: Vec<Vec<[Option<Reached>; 2]>> = Vec::with_capacity(wanted.len());
+
+    let mut first_cycle = vec![[None, None]; candidates.len()];
+    let initial_index = candidates
+        .iter()
+        .position(|value| *value == initial)
+        .ok_or_else(|| not_drawable(0))?;
+    if !fits(0, initial) {
+        return Err(not_drawable(0));
+    }
+    first_cycle[initial_index][0] = Some(Reached {
+        changes: 0,
+        previous: initial_index,
+        previous_changed: false,
+    });
+    table.push(first_cycle);
+
+    for cycle_index in 1..wanted.len() {
+        let before = &table[cycle_index - 1];
+        let mut current = vec![[None, None]; candidates.len()];
+
+        for (index, value) in candidates.iter().enumerate() {
+            if !fits(cycle_index, *value) {
+                continue;
+            }
+
+            current[index][0] = [false, true]
+                .into_iter()
+                .filter_map(|changed| {
+                    before[index][changed as usize].map(|reached| Reached {
+                        changes: reached.changes,
+                        previous: index,
+                        previous_changed: changed,
+                    })
+                })
+                .min_by_key(|reached| reached.changes);
+
+            if cycle_index >= 2 {
+                current[index][1] = before
+                    .iter()
+                    .enumerate()
+                    .filter(|(previous, _)| *previous != index)
+                    .filter_map(|(previous, reached)| {
+                        reached[0].map(|reached| Reached {
+                            changes: reached.changes + 1,
+                            previous,
+                            previous_changed: false,
+                        })
+                    })
+                    .min_by_key(|reached| reached.changes);
             }
         }
 
-        screen.push('\n');
+        if current.iter().flatten().all(|reached| reached.is_none()) {
+            return Err(not_drawable(cycle_index));
+        }
+
+        table.push(current);
     }
 
-    screen
+    let (mut index, mut changed) = table
+        .last()
+        .expect("At least one cycle")
+        .iter()
+        .enumerate()
+        .flat_map(|(index, reached)| [(index, false, reached[0]), (index, true, reached[1])])
+        .filter_map(|(index, changed, reached)| reached.map(|found| (index, changed, found)))
+        .min_by_key(|(_, _, reached)| reached.changes)
+        .map(|(index, changed, _)| (index, changed))
+        .expect("Last cycle has been checked to be reachable");
+
+    let mut values_x = Vec::with_capacity(wanted.len());
+    for cycle_index in (0..wanted.len()).rev() {
+        values_x.push(candidates[index]);
+        let reached = table[cycle_index][index][changed as usize]
+            .expect("Only reachable states are followed");
+        index = reached.previous;
+        changed = reached.previous_changed;
+    }
+    values_x.reverse();
+
+    Ok(values_x)
+}
+
+fn write_program_for(values_x: &[RegisterValue]) -> String {
+    let mut program: Vec<String> = Vec::new();
+    let mut next_cycle = 1;
+
+    for (cycle_index, pair) in values_x.windows(2).enumerate() {
+        let (before, after) = (pair[0], pair[1]);
+        if before == after {
+            continue;
+        }
+
+        // The addx takes the cycle with the old value and the one before.
+        let last_cycle_of_addx = cycle_index + 1;
+        for _ in next_cycle..(last_cycle_of_addx - 1) {
+            program.push("noop".to_string());
+        }
+        program.push(format!("addx {}", after - before));
+        next_cycle = last_cycle_of_addx + 1;
+    }
+
+    for _ in next_cycle..=values_x.len() {
+        program.push("noop".to_string());
+    }
+
+    program.join("\n")
+}
+
+fn draw_according_to_program(mut cpu: Cpu, config: &CrtConfig) -> Result<CrtPixels, CpuError> {
+    let mut crt = CrtScreen {
+        config: *config,
+        pixels: Vec::with_capacity(config.height),
+    };
+
+    cpu.run_observed(config.height * config.width, &mut [&mut crt])?;
+
+    Ok(crt.pixels)
 }
 
 fn process_program(
-    program: CpuProgram,
+    mut cpu: Cpu,
     up_to_cycle: usize,
     cycle_steps: usize,
     cycle_offset: usize,
-) -> i64 {
-    let mut signals: Vec<i64> = Default::default();
-    let mut cpu = Cpu::new(program);
-    let mut signal_counter = cycle_offset;
-    for current_cycle in 0..=up_to_cycle {
-        if (signal_counter % cycle_steps) == 0 {
-            let new_signal = (current_cycle as i64) * cpu.get_reg_v();
-            signals.push(new_signal);
+) -> Result<i64, CpuError> {
+    let mut signal = SignalStrength {
+        cycle_steps,
+        cycle_offset,
+        total: 0,
+    };
+
+    cpu.run_observed(up_to_cycle, &mut [&mut signal])?;
+
+    Ok(signal.total)
+}
+
+impl<F> CycleObserver for F
+where
+    F: FnMut(&CycleTrace) -> Result<(), RegisterError>,
+{
+    fn on_cycle(&mut self, trace: &CycleTrace) -> Result<(), RegisterError> {
+        self(trace)
+    }
+}
+
+impl CycleObserver for SignalStrength {
+    fn on_cycle(&mut self, trace: &CycleTrace) -> Result<(), RegisterError> {
+        if (trace.cycle + self.cycle_offset).is_multiple_of(self.cycle_steps) {
+            let strength = (trace.cycle as i64)
+                .checked_mul(trace.during.get(REGISTER_X)?)
+                .ok_or(RegisterError::Overflow)?;
+            self.total = self
+                .total
+                .checked_add(strength)
+                .ok_or(RegisterError::Overflow)?;
         }
 
-        cpu.next_cycle();
+        Ok(())
+    }
+}
+
+impl CycleObserver for CrtScreen {
+    fn on_cycle(&mut self, trace: &CycleTrace) -> Result<(), RegisterError> {
+        let x = (trace.cycle - 1) % self.config.width;
+        if x == 0 {
+            self.pixels.push(Vec::with_capacity(self.config.width));
+        }
+
+        let is_lit = self
+            .config
+            .is_covered_by_sprite(x, trace.during.get(REGISTER_X)?);
+        self.pixels
+            .last_mut()
+            .expect("Row is pushed at first column")
+            .push(is_lit);
 
-        signal_counter += 1;
+        Ok(())
     }
+}
 
-    signals
-        .into_iter()
-        .fold(i64::default(), |akk, signal| akk + signal)
+impl Default for CrtConfig {
+    fn default() -> Self {
+        Self {
+            height: 6,
+            width: 40,
+            sprite_width: 3,
+        }
+    }
 }
 
-fn parse_input(input: &str) -> CpuProgram {
-    input
-        .lines()
-        .map(|line| {
-            let mut inst_splitted = line.split(" ");
-
-            match (inst_splitted.next(), inst_splitted.next()) {
-                (Some("addx"), Some(amount)) => CpuInst::Addx(
-                    amount
-                        .parse()
-                        .expect("adding not  parsable to singed integer"),
-                ),
-                (Some("noop"), None) => CpuInst::Noop,
-                _ => panic!("line not parseable: {}", line),
+impl CrtConfig {
+    /// The sprite is centered around its position, an even sprite width
+    /// leans to the right.
+    fn is_covered_by_sprite(&self, x: usize, sprite_position: RegisterValue) -> bool {
+        let sprite_width = self.sprite_width as RegisterValue;
+        let sprite_start = sprite_position.saturating_sub((sprite_width - 1) / 2);
+        let x = x as RegisterValue;
+
+        x >= sprite_start && x < sprite_start.saturating_add(sprite_width)
+    }
+}
+
+impl Display for CycleTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let instruction = match &self.instruction {
+            Some(to_show) => to_show.to_string(),
+            None => "-".to_string(),
+        };
+
+        write!(
+            f,
+            "{:>5} | {:<12} | during {} | after {}",
+            self.cycle, instruction, self.during, self.after
+        )
+    }
+}
+
+impl InstructionSet {
+    /// Only the instructions and the register of the actual puzzle.
+    pub fn puzzle() -> Self {
+        Self::default()
+            .with_register(REGISTER_X, 1)
+            .with_instruction("noop", &[], 1, exec_noop)
+            .with_instruction("addx", &[OperandKind::Value], 2, exec_addx)
+    }
+
+    /// Puzzle instructions plus a second register, multiplication and jumps.
+    pub fn extended() -> Self {
+        Self::puzzle()
+            .with_register(REGISTER_Y, 0)
+            .with_instruction("addy", &[OperandKind::RegisterOrValue], 2, exec_addy)
+            .with_instruction("mulx", &[OperandKind::RegisterOrValue], 3, exec_mulx)
+            .with_instruction("jmp", &[OperandKind::Value], 1, exec_jmp)
+            .with_instruction(
+                "jnz",
+                &[OperandKind::Register, OperandKind::Value],
+                2,
+                exec_jnz,
+            )
+    }
+
+    pub fn with_register(mut self, name: RegisterName, initial_value: RegisterValue) -> Self {
+        self.registers.set(name, initial_value);
+        self
+    }
+
+    pub fn with_instruction(
+        mut self,
+        name: &str,
+        operands: &[OperandKind],
+        cycles: usize,
+        effect: InstEffect,
+    ) -> Self {
+        assert!(cycles > 0, "Instruction {name} needs at least one cycle");
+        self.definitions.insert(
+            name.to_string(),
+            InstDefinition {
+                operands: operands.to_vec(),
+                cycles,
+                effect,
+            },
+        );
+
+        self
+    }
+
+    pub fn get_initial_registers(&self) -> Registers {
+        self.registers.clone()
+    }
+
+    pub fn parse_program(&self, input: &str) -> Result<CpuProgram, ProgramParseError> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| self.parse_instruction(line, index + 1))
+            .collect()
+    }
+
+    fn parse_instruction(
+        &self,
+        line: &str,
+        line_number: usize,
+    ) -> Result<CpuInst, ProgramParseError> {
+        let words = parse_whole(&trimmed(separated(token(), spaces())), line).map_err(|error| {
+            ProgramParseError::Malformed {
+                line: line_number,
+                message: error.message,
             }
+        })?;
+        let mut parts = words.iter().map(String::as_str);
+        let name = parts.next().unwrap_or_default();
+        let raw_operands: Vec<&str> = parts.collect();
+
+        let definition =
+            self.definitions
+                .get(name)
+                .ok_or_else(|| ProgramParseError::UnknownInstruction {
+                    line: line_number,
+                    name: name.to_string(),
+                })?;
+
+        if raw_operands.len() != definition.operands.len() {
+            return Err(ProgramParseError::WrongOperandNumber {
+                line: line_number,
+                expected: definition.operands.len(),
+                found: raw_operands.len(),
+            });
+        }
+
+        let operands = raw_operands
+            .into_iter()
+            .zip(definition.operands.iter())
+            .map(|(raw, kind)| self.parse_operand(raw, *kind, line_number))
+            .collect::<Result<Vec<Operand>, ProgramParseError>>()?;
+
+        Ok(CpuInst {
+            name: name.to_string(),
+            operands,
+            cycles: definition.cycles,
+            effect: definition.effect,
         })
-        .collect()
+    }
+
+    fn parse_operand(
+        &self,
+        raw: &str,
+        kind: OperandKind,
+        line_number: usize,
+    ) -> Result<Operand, ProgramParseError> {
+        let as_value = parse_whole(&integer::<RegisterValue>(), raw).map(Operand::Value);
+
+        match (kind, as_value) {
+            (OperandKind::Value, Ok(value)) | (OperandKind::RegisterOrValue, Ok(value)) => {
+                Ok(value)
+            }
+            (OperandKind::Value, Err(_)) => Err(ProgramParseError::InvalidValue {
+                line: line_number,
+                operand: raw.to_string(),
+            }),
+            _ => {
+                let mut letters = raw.chars();
+                match (letters.next(), letters.next()) {
+                    (Some(name), None) if self.registers.has(name) => Ok(Operand::Register(name)),
+                    _ => Err(ProgramParseError::UnknownRegister {
+                        line: line_number,
+                        operand: raw.to_string(),
+                    }),
+                }
+            }
+        }
+    }
+}
+
+fn exec_noop(_: &mut Registers, _: &[Operand]) -> Result<ProgramFlow, RegisterError> {
+    Ok(ProgramFlow::Next)
+}
+
+fn exec_addx(
+    registers: &mut Registers,
+    operands: &[Operand],
+) -> Result<ProgramFlow, RegisterError> {
+    let to_add = registers.resolve(&operands[0])?;
+    registers.update(REGISTER_X, |value| value.checked_add(to_add))?;
+    Ok(ProgramFlow::Next)
+}
+
+fn exec_addy(
+    registers: &mut Registers,
+    operands: &[Operand],
+) -> Result<ProgramFlow, RegisterError> {
+    let to_add = registers.resolve(&operands[0])?;
+    registers.update(REGISTER_Y, |value| value.checked_add(to_add))?;
+    Ok(ProgramFlow::Next)
+}
+
+fn exec_mulx(
+    registers: &mut Registers,
+    operands: &[Operand],
+) -> Result<ProgramFlow, RegisterError> {
+    let factor = registers.resolve(&operands[0])?;
+    registers.update(REGISTER_X, |value| value.checked_mul(factor))?;
+    Ok(ProgramFlow::Next)
+}
+
+fn exec_jmp(registers: &mut Registers, operands: &[Operand]) -> Result<ProgramFlow, RegisterError> {
+    Ok(ProgramFlow::JumpBy(registers.resolve(&operands[0])?))
+}
+
+fn exec_jnz(registers: &mut Registers, operands: &[Operand]) -> Result<ProgramFlow, RegisterError> {
+    if registers.resolve(&operands[0])? != 0 {
+        Ok(ProgramFlow::JumpBy(registers.resolve(&operands[1])?))
+    } else {
+        Ok(ProgramFlow::Next)
+    }
+}
+
+impl Registers {
+    pub fn get(&self, name: RegisterName) -> Result<RegisterValue, RegisterError> {
+        self.0
+            .get(&name)
+            .copied()
+            .ok_or(RegisterError::Missing(name))
+    }
+
+    /// Replaces the value of an existing register. None from the change
+    /// means the new value does not fit into a register.
+    pub fn update(
+        &mut self,
+        name: RegisterName,
+        change: impl FnOnce(RegisterValue) -> Option<RegisterValue>,
+    ) -> Result<(), RegisterError> {
+        let changed = change(self.get(name)?).ok_or(RegisterError::Overflow)?;
+        self.set(name, changed);
+        Ok(())
+    }
+
+    pub fn set(&mut self, name: RegisterName, value: RegisterValue) {
+        self.0.insert(name, value);
+    }
+
+    pub fn has(&self, name: RegisterName) -> bool {
+        self.0.contains_key(&name)
+    }
+
+    pub fn resolve(&self, operand: &Operand) -> Result<RegisterValue, RegisterError> {
+        match operand {
+            Operand::Register(name) => self.get(*name),
+            Operand::Value(value) => Ok(*value),
+        }
+    }
+}
+
+impl From<ProgramParseError> for ProgramError {
+    fn from(error: ProgramParseError) -> Self {
+        ProgramError::Parse(error)
+    }
+}
+
+impl From<CpuError> for ProgramError {
+    fn from(error: CpuError) -> Self {
+        ProgramError::Runtime(error)
+    }
+}
+
+impl Display for ProgramParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramParseError::UnknownInstruction { line, name } => {
+                write!(f, "Line {}: Unknown instruction {}", line, name)
+            }
+            ProgramParseError::WrongOperandNumber {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Line {}: Expected {} operands but found {}",
+                line, expected, found
+            ),
+            ProgramParseError::InvalidValue { line, operand } => {
+                write!(f, "Line {}: {} is not a valid value", line, operand)
+            }
+            ProgramParseError::UnknownRegister { line, operand } => {
+                write!(f, "Line {}: {} is not a known register", line, operand)
+            }
+            ProgramParseError::Malformed { line, message } => {
+                write!(f, "Line {}: {}", line, message)
+            }
+        }
+    }
+}
+
+impl Display for RegisterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RegisterError::Missing(name) => write!(f, "No register with name {}", name),
+            RegisterError::Overflow => write!(f, "Value does not fit into a register"),
+        }
+    }
+}
+
+impl Display for ProgramError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProgramError::Parse(error) => write!(f, "{}", error),
+            ProgramError::Runtime(CpuError { cycle, error }) => {
+                write!(f, "Cycle {}: {}", cycle, error)
+            }
+        }
+    }
+}
+
+impl Display for Registers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let listed: Vec<String> = self
+            .0
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+
+        write!(f, "{}", listed.join(" "))
+    }
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Register(name) => write!(f, "{name}"),
+            Operand::Value(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+impl Display for CpuInst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        for operand in &self.operands {
+            write!(f, " {operand}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CpuInst {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_operands(&self) -> &[Operand] {
+        &self.operands
+    }
+
+    pub fn get_cycles(&self) -> usize {
+        self.cycles
+    }
 }
 
 impl Debug for Cpu {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct(stringify!(Cpu))
-            .field("X", &self.register)
-            .field("Instruction countdown", &self.inst_countdown)
+            .field("Registers", &self.registers)
+            .field("Cycle", &self.cycle)
+            .field("Cycles spent in instruction", &self.cycles_in_inst)
             .field("Current program line", &self.program_counter)
             .field("Terminated", &self.terminated)
             .finish()
@@ -111,56 +1020,333 @@ impl Debug for Cpu {
 }
 
 impl Cpu {
-    fn new(program: CpuProgram) -> Self {
-        let mut new_self = Self {
-            program,
-            terminated: false,
-            register: 1,
+    pub fn new(program: CpuProgram, registers: Registers) -> Self {
+        let terminated = program.is_empty();
+
+        Self {
+            registers,
             program_counter: 0,
-            inst_countdown: 0,
-        };
+            program,
+            cycles_in_inst: 0,
+            cycle: 0,
+            terminated,
+        }
+    }
+
+    /// Completes one cycle. Registers reflect the state after this cycle,
+    /// which is the value seen during the following one. The clock keeps
+    /// running after termination, the registers just do not change anymore.
+    /// An instruction which fails leaves the registers as they were.
+    pub fn next_cycle(&mut self) -> Result<(), CpuError> {
+        self.cycle += 1;
+        if self.terminated {
+            return Ok(());
+        }
+
+        self.cycles_in_inst += 1;
+
+        let current = &self.program[self.program_counter];
+        if self.cycles_in_inst < current.cycles {
+            return Ok(());
+        }
 
-        new_self._adjust_inst_countdown();
+        let flow =
+            (current.effect)(&mut self.registers, &current.operands).map_err(|error| CpuError {
+                cycle: self.cycle,
+                error,
+            })?;
+        self.cycles_in_inst = 0;
+        self.move_program_counter(flow);
 
-        new_self
+        Ok(())
     }
 
-    fn next_cycle(&mut self) {
-        if self.terminated {
-            return;
-        } else if self.inst_countdown != 0 {
-            self.inst_countdown -= 1;
+    pub fn step(&mut self) -> Result<CycleTrace, CpuError> {
+        let during = self.registers.clone();
+        let instruction = if self.terminated {
+            None
         } else {
-            let to_execute = &self.program[self.program_counter];
+            Some(self.program[self.program_counter].clone())
+        };
 
-            match to_execute {
-                CpuInst::Noop => (),
-                CpuInst::Addx(to_add) => {
-                    self.register += to_add;
-                }
-            };
+        self.next_cycle()?;
+
+        Ok(CycleTrace {
+            cycle: self.cycle,
+            during,
+            after: self.registers.clone(),
+            instruction,
+        })
+    }
+
+    /// Cycles until the program terminates or the first error.
+    pub fn trace(&mut self) -> impl Iterator<Item = Result<CycleTrace, CpuError>> + '_ {
+        let mut failed = false;
+        iter::from_fn(move || {
+            if self.terminated || failed {
+                return None;
+            }
+
+            let traced = self.step();
+            failed = traced.is_err();
+            Some(traced)
+        })
+    }
 
-            self.program_counter += 1;
-            self.terminated = self.program_counter >= self.program.len();
-            if self.terminated {
-                return;
+    /// Runs exactly the given number of cycles, even beyond the termination
+    /// of the program, and reports every cycle to all observers.
+    pub fn run_observed(
+        &mut self,
+        cycles: usize,
+        observers: &mut [&mut dyn CycleObserver],
+    ) -> Result<(), CpuError> {
+        for _ in 0..cycles {
+            let trace = self.step()?;
+            for observer in observers.iter_mut() {
+                observer.on_cycle(&trace).map_err(|error| CpuError {
+                    cycle: trace.cycle,
+                    error,
+                })?;
             }
+        }
+
+        Ok(())
+    }
+
+    pub fn get_register(&self, name: RegisterName) -> Result<RegisterValue, RegisterError> {
+        self.registers.get(name)
+    }
+
+    pub fn get_registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn get_cycle(&self) -> usize {
+        self.cycle
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.terminated
+    }
 
-            self._adjust_inst_countdown();
-            self.inst_countdown -= 1;
+    fn move_program_counter(&mut self, flow: ProgramFlow) {
+        let offset = match flow {
+            ProgramFlow::Next => 1,
+            ProgramFlow::JumpBy(offset) => offset,
+        };
+
+        // Jumping too far to even compute the target leaves the program too.
+        match (self.program_counter as RegisterValue).checked_add(offset) {
+            Some(next_counter)
+                if next_counter >= 0 && (next_counter as usize) < self.program.len() =>
+            {
+                self.program_counter = next_counter as usize;
+            }
+            _ => self.terminated = true,
         }
     }
+}
+
+#[cfg(test)]
+mod testing {
 
-    fn _adjust_inst_countdown(&mut self) {
-        let next_instruction = &self.program[self.program_counter];
+    use super::*;
 
-        self.inst_countdown = match next_instruction {
-            CpuInst::Noop => 1,
-            CpuInst::Addx(_) => 2,
+    fn run_cycles(program: &str, cycles: usize) -> Cpu {
+        let instruction_set = InstructionSet::extended();
+        let program = instruction_set.parse_program(program).unwrap();
+        let mut cpu = Cpu::new(program, instruction_set.get_initial_registers());
+        for _ in 0..cycles {
+            cpu.next_cycle().unwrap();
         }
+
+        cpu
+    }
+
+    #[test]
+    fn test_addx_takes_two_cycles() {
+        // Set up
+        let input = "noop\naddx 3\naddx -5";
+
+        // Act
+        let after_three = run_cycles(input, 3);
+        let after_five = run_cycles(input, 5);
+
+        // Assert
+        assert_eq!(4, after_three.get_register(REGISTER_X).unwrap());
+        assert_eq!(-1, after_five.get_register(REGISTER_X).unwrap());
+        assert!(after_five.is_terminated());
+    }
+
+    #[test]
+    fn test_jnz_loops_until_register_is_zero() {
+        // Set up
+        let input = "addy 3\naddx 2\naddy -1\njnz Y -2";
+
+        // Act
+        let cpu = run_cycles(input, 100);
+
+        // Assert
+        assert_eq!(7, cpu.get_register(REGISTER_X).unwrap());
+        assert_eq!(0, cpu.get_register(REGISTER_Y).unwrap());
+        assert!(cpu.is_terminated());
+    }
+
+    #[test]
+    fn test_trace_reports_during_and_after() {
+        // Set up
+        let instruction_set = InstructionSet::puzzle();
+        let program = instruction_set.parse_program("noop\naddx 3").unwrap();
+        let mut cpu = Cpu::new(program, instruction_set.get_initial_registers());
+
+        // Act
+        let actual: Vec<(usize, RegisterValue, RegisterValue)> = cpu
+            .trace()
+            .map(|cycle| {
+                let cycle = cycle.unwrap();
+                (
+                    cycle.cycle,
+                    cycle.during.get(REGISTER_X).unwrap(),
+                    cycle.after.get(REGISTER_X).unwrap(),
+                )
+            })
+            .collect();
+
+        // Assert
+        assert_eq!(vec![(1, 1, 1), (2, 1, 1), (3, 1, 4)], actual);
+    }
+
+    #[test]
+    fn test_trace_stops_endless_program() {
+        // Act
+        let actual = get_trace("noop\njmp 0", 5);
+
+        // Assert
+        assert_eq!(6, actual.lines().count());
+        assert_eq!(
+            Some("Stopped after 5 cycles, the program did not terminate"),
+            actual.lines().last()
+        );
+    }
+
+    #[test]
+    fn test_runtime_errors() {
+        // Set up
+        let without_x = InstructionSet::default()
+            .with_register(REGISTER_Y, 0)
+            .with_instruction("addx", &[OperandKind::Value], 2, exec_addx);
+
+        // Act
+        let overflow = get_trace_with(
+            "addx 9223372036854775806\nmulx 2",
+            &InstructionSet::extended(),
+            100,
+        );
+        let missing_in_effect = get_trace_with("addx 1", &without_x, 100);
+        let missing_in_observer = get_pixels_with("addx 1", &without_x, &CrtConfig::default());
+
+        // Assert
+        assert_eq!(
+            Err(ProgramError::Runtime(CpuError {
+                cycle: 5,
+                error: RegisterError::Overflow
+            })),
+            overflow
+        );
+        assert_eq!(
+            Err(ProgramError::Runtime(CpuError {
+                cycle: 2,
+                error: RegisterError::Missing(REGISTER_X)
+            })),
+            missing_in_effect
+        );
+        assert_eq!(
+            Err(ProgramError::Runtime(CpuError {
+                cycle: 1,
+                error: RegisterError::Missing(REGISTER_X)
+            })),
+            missing_in_observer
+        );
+    }
+
+    #[test]
+    fn test_annotation_stops_endless_program() {
+        // Act
+        let actual = annotate_program("addx 2\njmp -1", 4);
+
+        // Assert
+        assert_eq!(4, actual.lines().count());
+        assert_eq!(
+            Some("Stopped after 4 cycles, the program did not terminate"),
+            actual.lines().last()
+        );
+    }
+
+    #[test]
+    fn test_wide_sprite_and_pbm() {
+        // Set up
+        let config = CrtConfig {
+            height: 1,
+            width: 6,
+            sprite_width: 4,
+        };
+
+        // Act
+        let pixels = get_pixels("noop\nnoop\nnoop\nnoop\nnoop\nnoop", &config);
+
+        // Assert
+        assert_eq!("P1\n6 1\n1 1 1 1 0 0\n", render_pbm(&pixels));
     }
 
-    fn get_reg_v(&self) -> i64 {
-        self.register
+    #[test]
+    fn test_assembled_program_draws_pixels() {
+        // Set up
+        let config = CrtConfig {
+            height: 2,
+            width: 10,
+            sprite_width: 3,
+        };
+        let pixels = parse_pixels("##...###..\n.#....####", '#');
+
+        // Act
+        let program = assemble_drawing(&pixels, &config).unwrap();
+        let drawn = get_pixels(&program, &config);
+
+        // Assert
+        assert_eq!(pixels, drawn);
+    }
+
+    #[test]
+    fn test_first_pixel_can_not_be_dark() {
+        // Set up
+        let config = CrtConfig {
+            height: 1,
+            width: 4,
+            sprite_width: 3,
+        };
+        let pixels = parse_pixels(".##.", '#');
+
+        // Act
+        let actual = assemble_drawing(&pixels, &config);
+
+        // Assert
+        assert_eq!(Err(AssembleError::NotDrawable { x: 0, y: 0 }), actual);
+    }
+
+    #[test]
+    fn test_parse_error_has_line() {
+        // Act
+        let actual = InstructionSet::puzzle().parse_program("noop\nmulx 2");
+        let trailing_whitespace = InstructionSet::puzzle().parse_program("noop \naddx 3\t");
+
+        // Assert
+        assert_eq!(
+            Err(ProgramParseError::UnknownInstruction {
+                line: 2,
+                name: "mulx".to_string()
+            }),
+            actual.map(|_| ())
+        );
+        assert_eq!(Ok(2), trailing_whitespace.map(|program| program.len()));
     }
 }
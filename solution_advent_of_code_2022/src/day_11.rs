@@ -1,43 +1,135 @@
-use crate::advent_math;
-use crate::parsing;
+use crate::advent_math::{self, Integer};
+use crate::parsing::{self, ParseError};
+use core::cmp::Ordering;
+use core::fmt::{Debug, Display};
 use core::str::FromStr;
-use std::collections::VecDeque;
+use num_bigint::BigUint;
+use std::collections::{HashMap, VecDeque};
 
 type AmountUnit = usize;
+type ConditionFromAmount = fn(AmountUnit) -> Condition;
 
-#[derive(Debug)]
-enum OpAmount {
-    Old,
-    Amount(AmountUnit),
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Add,
+    Sub,
+    Mult,
+    Div,
+    Rem,
 }
 
-#[derive(Debug)]
+/// Expression on the right side of "new = ", like "old * (old + 3)".
+#[derive(Debug, Clone, PartialEq, Eq)]
 enum Operation {
-    Add(OpAmount),
-    Mult(OpAmount),
+    Old,
+    Amount(AmountUnit),
+    Binary(Box<Operation>, Operator, Box<Operation>),
 }
 #[derive(Debug)]
 struct OperationParseErr {
     _error_message: &'static str,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OperationToken {
+    Old,
+    Amount(AmountUnit),
+    Operator(Operator),
+    OpenParen,
+    CloseParen,
+}
+
+/// A worry level left the range of numbers, by an overflow, going below
+/// zero or a division by zero.
+#[derive(Debug, PartialEq, Eq)]
+pub struct WorryOverflow {
+    pub monkey: AmountUnit,
+    pub round: usize,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum MonkeyError {
+    InvalidNotes(ParseError),
+    InvalidGraph(Vec<MonkeyGraphError>),
+    Overflow(WorryOverflow),
+    /// Test or operation of the monkey can not be calculated under a common
+    /// modular, like a "greater than" test.
+    NoCommonModular {
+        monkey: AmountUnit,
+    },
+    CommonModularTooLarge,
+    /// Reports exist only for the rounds from 1 to the last one played.
+    ReportRoundOutOfRange {
+        round: usize,
+        last_round: usize,
+    },
+    /// Product of the inspections of the top monkeys is too large.
+    BusinessOverflow,
+    /// Inspections of a monkey can not be counted for that many rounds.
+    InspectionOverflow,
+}
+
+/// Problem with a monkey block which would break the simulation.
+/// Blocks are counted from zero like the monkey ids.
+#[derive(Debug, PartialEq, Eq)]
+pub enum MonkeyGraphError {
+    IdNotAtPosition { block: usize, id: AmountUnit },
+    UnknownTarget { block: usize, target: AmountUnit },
+    ThrowsToItself { block: usize },
+    DivisorIsZero { block: usize },
+}
+
+/// How the worry level of an item drops after a monkey inspected it without
+/// damaging it.
+pub enum Relief<W> {
+    None,
+    DivideBy(AmountUnit),
+    Custom(fn(W) -> W),
+}
+
+/// Worry level of an item. Implemented for fixed size numbers with checked
+/// arithmetic and for numbers of arbitrary size.
+trait Worry: Clone + Debug {
+    fn from_amount(amount: AmountUnit) -> Self;
+    fn checked_calc(operator: Operator, left: Self, right: Self) -> Option<Self>;
+    fn remainder_by(&self, divisor: AmountUnit) -> AmountUnit;
+    fn compare_to(&self, amount: AmountUnit) -> Ordering;
+}
+
 #[derive(Debug)]
-struct ItemThrown {
-    item: AmountUnit,
+struct ItemThrown<W> {
+    item: W,
     monkey_thrown_to: AmountUnit,
 }
 
+/// State of all monkeys after a round like the puzzle describes it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundReport<W> {
+    pub round: usize,
+    pub holdings: Vec<Vec<W>>,
+    pub inspections: Vec<AmountUnit>,
+}
+
+/// Condition after "Test: " which decides where an item is thrown to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Condition {
+    DivisibleBy(AmountUnit),
+    GreaterThan(AmountUnit),
+    LessThan(AmountUnit),
+    Equals(AmountUnit),
+}
+
 #[derive(Debug)]
 struct Test {
-    divider: AmountUnit,
+    condition: Condition,
     thrown_if_true: AmountUnit,
     thrown_if_false: AmountUnit,
 }
 
 #[derive(Debug)]
-struct Monkey {
-    _id: AmountUnit,
-    items: VecDeque<AmountUnit>,
+struct Monkey<W> {
+    id: AmountUnit,
+    items: VecDeque<W>,
     operation: Operation,
     test_logic: Test,
     number_inspection: AmountUnit,
@@ -45,165 +137,454 @@ struct Monkey {
 }
 
 pub fn calc_top_2_inspecting_number(input: &str) -> AmountUnit {
-    let mut monkeys = parse_input(input);
-
-    get_top_inspection_num_form(&mut monkeys, 2, 20)
+    calc_top_inspecting_number_checked(input, 2, 20, &Relief::DivideBy(3))
+        .unwrap_or_else(|overflow| panic!("{}", overflow))
 }
 
-// == After round 1 ==
-// Monkey 0 inspected items 2 times.
-// Monkey 1 inspected items 4 times.
-// Monkey 2 inspected items 3 times.
-// Monkey 3 inspected items 6 times.
 pub fn calc_top_2_inspecting_number_no_relief(input: &str) -> AmountUnit {
-    let mut monkeys = parse_input(input);
-    let lcm = get_lcm_from(&monkeys);
-    for to_set_modular in monkeys.iter_mut() {
-        to_set_modular.set_common_modular(lcm);
+    calc_top_inspecting_number_no_relief(input, 2, 10_000)
+        .unwrap_or_else(|overflow| panic!("{}", overflow))
+}
+
+/// Calculates under the common modular if the monkeys allow it and falls
+/// back to the plain checked simulation otherwise.
+pub fn calc_top_inspecting_number_no_relief(
+    input: &str,
+    top_number: usize,
+    rounds: usize,
+) -> Result<AmountUnit, MonkeyError> {
+    let mut monkeys: VecDeque<Monkey<AmountUnit>> = parse_input(input)?;
+    if supports_common_modular(&monkeys) {
+        set_up_common_modular(&mut monkeys).expect("Support is checked before");
     }
 
-    get_top_inspection_num_form(&mut monkeys, 2, 10_000)
+    get_top_inspection_num_form(&mut monkeys, top_number, rounds, &Relief::None)
 }
 
-fn get_top_inspection_num_form(
-    monkeys: &mut VecDeque<Monkey>,
+/// Worry levels are kept below the least common multiple of all test
+/// dividers. Only valid without relief and if every test checks for
+/// divisibility.
+pub fn calc_top_inspecting_number_modular(
+    input: &str,
+    top_number: usize,
+    rounds: usize,
+) -> Result<AmountUnit, MonkeyError> {
+    let mut monkeys: VecDeque<Monkey<AmountUnit>> = parse_input(input)?;
+    set_up_common_modular(&mut monkeys)?;
+
+    get_top_inspection_num_form(&mut monkeys, top_number, rounds, &Relief::None)
+}
+
+/// Same result as `calc_top_inspecting_number_modular` but in sublinear time
+/// for huge numbers of rounds. Items do not influence each other, so every
+/// item follows its own path through the monkeys. Under the common modular
+/// an item has only finitely many states, so its path repeats at some point
+/// and the inspections of the remaining rounds are extrapolated from the cycle.
+/// The monkey business is returned as a wider number since the product of
+/// inspections over that many rounds does not fit into `AmountUnit`.
+pub fn calc_top_inspecting_number_fast_forward(
+    input: &str,
+    top_number: usize,
+    rounds: usize,
+) -> Result<u128, MonkeyError> {
+    let mut monkeys: VecDeque<Monkey<AmountUnit>> = parse_input(input)?;
+    let modular = set_up_common_modular(&mut monkeys)?;
+
+    let mut number_inspections = vec![0u128; monkeys.len()];
+    for (monkey, items) in monkeys.iter().enumerate() {
+        for item in items.items.iter() {
+            let by_item = count_inspections_of_item(&monkeys, monkey, *item, modular, rounds)?;
+            for (total, from_item) in number_inspections.iter_mut().zip(by_item) {
+                *total = total
+                    .checked_add(from_item)
+                    .ok_or(MonkeyError::InspectionOverflow)?;
+            }
+        }
+    }
+
+    multiply_top(number_inspections, top_number)
+}
+
+/// Reports for the given rounds out of the rounds 1 to `last_round`. Without
+/// relief worry levels are calculated under the common modular if the
+/// monkeys allow it, so holdings show remainders in that case.
+pub fn get_round_reports(
+    input: &str,
+    rounds: &[usize],
+    last_round: usize,
+    relief: &Relief<AmountUnit>,
+) -> Result<Vec<RoundReport<AmountUnit>>, MonkeyError> {
+    if let Some(&round) = rounds
+        .iter()
+        .find(|round| **round == 0 || **round > last_round)
+    {
+        return Err(MonkeyError::ReportRoundOutOfRange { round, last_round });
+    }
+    let last_report = match rounds.iter().max() {
+        Some(last) => *last,
+        None => return Ok(Vec::new()),
+    };
+
+    let mut monkeys: VecDeque<Monkey<AmountUnit>> = parse_input(input)?;
+    if matches!(relief, Relief::None) && supports_common_modular(&monkeys) {
+        set_up_common_modular(&mut monkeys).expect("Support is checked before");
+    }
+
+    let mut reports = Vec::with_capacity(rounds.len());
+    go_nth_rounds(&mut monkeys, last_report, relief, &mut |round, monkeys| {
+        if rounds.contains(&round) {
+            reports.push(RoundReport::from_monkeys(round, monkeys));
+        }
+    })?;
+
+    Ok(reports)
+}
+
+/// Plain simulation with fixed size numbers which reports an overflow
+/// instead of wrapping around.
+pub fn calc_top_inspecting_number_checked(
+    input: &str,
+    top_number: usize,
+    rounds: usize,
+    relief: &Relief<AmountUnit>,
+) -> Result<AmountUnit, MonkeyError> {
+    let mut monkeys: VecDeque<Monkey<AmountUnit>> = parse_input(input)?;
+
+    get_top_inspection_num_form(&mut monkeys, top_number, rounds, relief)
+}
+
+/// Exact simulation with numbers of arbitrary size. Worry levels can grow
+/// very fast without relief, so this is only feasible for a few rounds.
+pub fn calc_top_inspecting_number_exact(
+    input: &str,
+    top_number: usize,
+    rounds: usize,
+    relief: &Relief<BigUint>,
+) -> Result<AmountUnit, MonkeyError> {
+    let mut monkeys: VecDeque<Monkey<BigUint>> = parse_input(input)?;
+
+    get_top_inspection_num_form(&mut monkeys, top_number, rounds, relief)
+}
+
+fn get_top_inspection_num_form<W: Worry>(
+    monkeys: &mut VecDeque<Monkey<W>>,
     top_number: usize,
     round: usize,
-) -> AmountUnit {
-    go_nth_rounds(monkeys, round);
-    let mut number_inspections: Vec<AmountUnit> = monkeys
-        .into_iter()
+    relief: &Relief<W>,
+) -> Result<AmountUnit, MonkeyError> {
+    go_nth_rounds(monkeys, round, relief, &mut |_, _| {})?;
+    let number_inspections: Vec<AmountUnit> = monkeys
+        .iter_mut()
         .map(|monkey| monkey.number_inspection)
         .collect();
 
+    multiply_top(number_inspections, top_number)
+}
+
+/// Product of the given number of highest inspection counts.
+fn multiply_top<T: Integer>(
+    mut number_inspections: Vec<T>,
+    top_number: usize,
+) -> Result<T, MonkeyError> {
     number_inspections.sort();
     number_inspections.reverse();
 
-    number_inspections.into_iter().take(top_number).product()
+    number_inspections
+        .into_iter()
+        .take(top_number)
+        .try_fold(T::ONE, |product, next| product.checked_mul(next))
+        .ok_or(MonkeyError::BusinessOverflow)
+}
+
+/// Least common multiple of all test dividers, None if it is too large.
+fn get_common_modular<W>(monkeys: &VecDeque<Monkey<W>>) -> Option<AmountUnit> {
+    let dividers = monkeys
+        .iter()
+        .filter_map(|monkey| match monkey.test_logic.condition {
+            Condition::DivisibleBy(divider) => Some(divider),
+            _ => None,
+        });
+
+    advent_math::lcm_of(dividers).ok()
 }
 
-fn get_lcm_from(monkeys: &VecDeque<Monkey>) -> AmountUnit {
-    let mut modulars = monkeys.iter().map(|monkey| monkey.test_logic.divider);
+/// Comparisons like "greater than" are not preserved by remainders, only
+/// divisibility by a divider of the common modular is.
+fn supports_common_modular<W>(monkeys: &VecDeque<Monkey<W>>) -> bool {
+    let only_divisibility = monkeys
+        .iter()
+        .all(|monkey| matches!(monkey.test_logic.condition, Condition::DivisibleBy(_)));
 
-    let mut lcm = modulars.next().unwrap();
-    for current_modular in modulars {
-        lcm = advent_math::get_lcm(lcm, current_modular);
+    only_divisibility
+        && get_common_modular(monkeys).is_some_and(|lcm| {
+            monkeys
+                .iter()
+                .all(|monkey| monkey.operation.supports_modular(lcm))
+        })
+}
+
+/// Returns the common modular the monkeys calculate under from now on.
+fn set_up_common_modular(
+    monkeys: &mut VecDeque<Monkey<AmountUnit>>,
+) -> Result<AmountUnit, MonkeyError> {
+    if let Some(not_divisible) = monkeys
+        .iter()
+        .find(|monkey| !matches!(monkey.test_logic.condition, Condition::DivisibleBy(_)))
+    {
+        return Err(MonkeyError::NoCommonModular {
+            monkey: not_divisible.id,
+        });
+    }
+    let lcm = get_common_modular(monkeys).ok_or(MonkeyError::CommonModularTooLarge)?;
+    if let Some(unsupported) = monkeys
+        .iter()
+        .find(|monkey| !monkey.operation.supports_modular(lcm))
+    {
+        return Err(MonkeyError::NoCommonModular {
+            monkey: unsupported.id,
+        });
+    }
+    for to_set_modular in monkeys.iter_mut() {
+        to_set_modular.set_common_modular(lcm);
     }
 
-    lcm
+    Ok(lcm)
 }
 
-fn go_nth_rounds(monkeys: &mut VecDeque<Monkey>, round_number: AmountUnit) {
-    for _ in 0..round_number {
+fn go_nth_rounds<W: Worry>(
+    monkeys: &mut VecDeque<Monkey<W>>,
+    round_number: AmountUnit,
+    relief: &Relief<W>,
+    after_round: &mut dyn FnMut(usize, &VecDeque<Monkey<W>>),
+) -> Result<(), WorryOverflow> {
+    for round in 1..=round_number {
         for current_monkey in 0..monkeys.len() {
-            let thrown_items = monkeys[current_monkey].inpect_next_round();
+            let thrown_items =
+                monkeys[current_monkey]
+                    .inpect_next_round(relief)
+                    .ok_or(WorryOverflow {
+                        monkey: monkeys[current_monkey].id,
+                        round,
+                    })?;
             for next_throw in thrown_items {
                 monkeys[next_throw.monkey_thrown_to].give_thrown_item(next_throw.item);
             }
         }
+
+        after_round(round, monkeys);
     }
+
+    Ok(())
 }
 
-fn parse_input(input: &str) -> VecDeque<Monkey> {
-    let sections = section_by_empty_line(input);
+/// Inspections per monkey caused by a single item within the given rounds.
+fn count_inspections_of_item(
+    monkeys: &VecDeque<Monkey<AmountUnit>>,
+    monkey: AmountUnit,
+    item: AmountUnit,
+    modular: usize,
+    rounds: usize,
+) -> Result<Vec<u128>, MonkeyError> {
+    // Inspections summed up to the start of the round at the index.
+    let mut totals: Vec<Vec<AmountUnit>> = vec![vec![0; monkeys.len()]];
+    // Round at whose start the item was in a certain state.
+    let mut seen: HashMap<(AmountUnit, AmountUnit), usize> = HashMap::new();
+    let mut state = (monkey, item % modular);
+
+    for round in 0..rounds {
+        if let Some(&cycle_start) = seen.get(&state) {
+            let cycle_length = round - cycle_start;
+            let full_cycles = (rounds - cycle_start) / cycle_length;
+            let rest = (rounds - cycle_start) % cycle_length;
+
+            let before_cycle = &totals[cycle_start];
+            let after_cycle = &totals[round];
+            let after_rest = &totals[cycle_start + rest];
+
+            return (0..monkeys.len())
+                .map(|id| {
+                    let per_cycle = (after_cycle[id] - before_cycle[id]) as u128;
+                    (full_cycles as u128)
+                        .checked_mul(per_cycle)
+                        .and_then(|in_cycles| in_cycles.checked_add(after_rest[id] as u128))
+                        .ok_or(MonkeyError::InspectionOverflow)
+                })
+                .collect();
+        }
+        seen.insert(state, round);
+
+        let mut inspected = totals[round].clone();
+        state =
+            move_item_one_round(monkeys, state, modular, &mut inspected).ok_or(WorryOverflow {
+                monkey: state.0,
+                round: round + 1,
+            })?;
+        totals.push(inspected);
+    }
 
-    sections
+    Ok(totals
+        .pop()
+        .expect("Totals start with the state before round 1")
         .into_iter()
-        .map(|current_section| {
-            let mut lines = current_section.into_iter();
-
-            let line_monkey_id = lines.next().expect("No lines for monkey id");
-            let monkey_id: AmountUnit =
-                parsing::get_seq_from_regex(r"Monkey (\d+):", &line_monkey_id, 1)
-                    .expect("Could not parse out monkey id")[0]
-                    .parse()
-                    .expect("Could not parse monkey id to number");
-
-            let line_starting_item = lines.next().expect("No line for starting items");
-            let starting_items_comma_sep =
-                parsing::strip_away_left_part(&line_starting_item, "Starting items: ");
-            let items_to_start_with: Vec<AmountUnit> =
-                parsing::get_parsed_sep_by(&starting_items_comma_sep, ", ");
-
-            let raw_opration = lines.next().expect("No line for operation");
-            let operation_stripped =
-                parsing::strip_away_left_part(&raw_opration, "Operation: new = old ");
-
-            let operations: Operation = operation_stripped
-                .parse()
-                .expect("Could not parse operation");
-
-            let test_raw_divide = lines.next().expect("no line for test divide");
-            let test_divide_stripped =
-                parsing::strip_away_left_part(test_raw_divide, "Test: divisible by ");
-
-            let test_amount_divide: AmountUnit = test_divide_stripped
-                .parse()
-                .expect("Test divide amount not parsable into number");
-
-            let (thrown_true, thrown_false): (AmountUnit, AmountUnit) =
-                match (lines.next(), lines.next()) {
-                    (Some(true_line), Some(false_line)) => {
-                        let line_true_stripped =
-                            parsing::strip_away_left_part(&true_line, "If true: throw to monkey ");
-                        let line_false_stripped = parsing::strip_away_left_part(
-                            &false_line,
-                            "If false: throw to monkey ",
-                        );
-
-                        (
-                            line_true_stripped.parse().expect("Could parse into number"),
-                            line_false_stripped
-                                .parse()
-                                .expect("Could parse into number"),
-                        )
-                    }
-                    _ => panic!("No true and false line logic line"),
-                };
+        .map(|number| number as u128)
+        .collect())
+}
 
-            let monkey_test_logic = Test::new(test_amount_divide, thrown_true, thrown_false);
+/// Follows an item from the monkey which holds it at the start of a round
+/// until it is thrown to a monkey which has already had its turn.
+fn move_item_one_round(
+    monkeys: &VecDeque<Monkey<AmountUnit>>,
+    (mut monkey, mut item): (AmountUnit, AmountUnit),
+    modular: usize,
+    inspected: &mut [AmountUnit],
+) -> Option<(AmountUnit, AmountUnit)> {
+    loop {
+        let current = &monkeys[monkey];
+        item = current.operation.apply_with_mod(item, modular)?;
+        inspected[monkey] += 1;
 
-            Monkey::new(
-                monkey_id,
-                items_to_start_with.into_iter().collect(),
-                operations,
-                monkey_test_logic,
-            )
-        })
-        .collect()
+        let thrown_to = current.test_logic.return_right_value(&item);
+        if thrown_to <= monkey {
+            return Some((thrown_to, item));
+        }
+        monkey = thrown_to;
+    }
 }
 
-fn section_by_empty_line(to_section: &str) -> Vec<Vec<&str>> {
-    let mut current_monkey = 0;
-    let mut sections: Vec<Vec<&str>> = vec![vec![]];
+/// Checks the notes for all problems at once before a simulation is started.
+pub fn validate_notes(input: &str) -> Result<(), MonkeyError> {
+    let monkeys: VecDeque<Monkey<AmountUnit>> = parse_unvalidated_input(input)?;
+    validate_monkeys(&monkeys).map_err(MonkeyError::InvalidGraph)
+}
 
-    for line in to_section.lines() {
-        if line.is_empty() {
-            sections.push(vec![]);
-            current_monkey += 1;
-        } else {
-            sections[current_monkey].push(line);
+fn validate_monkeys<W>(monkeys: &VecDeque<Monkey<W>>) -> Result<(), Vec<MonkeyGraphError>> {
+    let mut errors = Vec::new();
+
+    for (block, monkey) in monkeys.iter().enumerate() {
+        if monkey.id != block {
+            errors.push(MonkeyGraphError::IdNotAtPosition {
+                block,
+                id: monkey.id,
+            });
+        }
+
+        if monkey.test_logic.condition == Condition::DivisibleBy(0) {
+            errors.push(MonkeyGraphError::DivisorIsZero { block });
+        }
+
+        let mut targets = vec![
+            monkey.test_logic.thrown_if_true,
+            monkey.test_logic.thrown_if_false,
+        ];
+        targets.dedup();
+        for target in targets {
+            if target == block {
+                errors.push(MonkeyGraphError::ThrowsToItself { block });
+            } else if target >= monkeys.len() {
+                errors.push(MonkeyGraphError::UnknownTarget { block, target });
+            }
         }
     }
 
-    sections
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// Fails with all problems found by `validate_notes` at once.
+fn parse_input<W: Worry>(input: &str) -> Result<VecDeque<Monkey<W>>, MonkeyError> {
+    let monkeys = parse_unvalidated_input(input)?;
+    validate_monkeys(&monkeys).map_err(MonkeyError::InvalidGraph)?;
+
+    Ok(monkeys)
+}
+
+fn parse_unvalidated_input<W: Worry>(input: &str) -> Result<VecDeque<Monkey<W>>, ParseError> {
+    parsing::split_blocks(input)
+        .into_iter()
+        .map(|current_section| {
+            let mut lines = current_section
+                .lines
+                .into_iter()
+                .enumerate()
+                .map(|(offset, line)| (current_section.first_line + offset, line));
+            let mut next_line = |prefix: &str| match lines.next() {
+                Some((line_number, line)) => parsing::try_strip_away_left_part(line, prefix)
+                    .map(|stripped| (line_number, stripped))
+                    .map_err(|error| error.at_line(line_number)),
+                None => Err(ParseError::new(format!(
+                    "Monkey block from line {} has no line \"{}\"",
+                    current_section.first_line, prefix
+                ))),
+            };
+
+            let (line_number, raw_monkey_id) = next_line("Monkey ")?;
+            let monkey_id: AmountUnit = raw_monkey_id
+                .strip_suffix(':')
+                .ok_or_else(|| {
+                    ParseError::new("Monkey id has to end with \":\"").at_line(line_number)
+                })
+                .and_then(parsing::try_parse)
+                .map_err(|error| error.at_line(line_number))?;
+
+            let (line_number, starting_items_comma_sep) = next_line("Starting items:")?;
+            let items_to_start_with: Vec<AmountUnit> = if starting_items_comma_sep.is_empty() {
+                Vec::new()
+            } else {
+                parsing::try_parsed_sep_by(starting_items_comma_sep.trim(), ", ")
+                    .map_err(|error| error.at_line(line_number))?
+            };
+
+            let (line_number, operation_stripped) = next_line("Operation: new = ")?;
+            let operations: Operation = operation_stripped.parse().map_err(|_| {
+                ParseError::new(format!(
+                    "Could not parse operation \"{}\"",
+                    operation_stripped
+                ))
+                .at_line(line_number)
+            })?;
+
+            let (line_number, raw_condition) = next_line("Test: ")?;
+            let test_condition: Condition = raw_condition.parse().map_err(|_| {
+                ParseError::new(format!("Could not parse test \"{}\"", raw_condition))
+                    .at_line(line_number)
+            })?;
+
+            let (line_number, raw_true) = next_line("If true: throw to monkey ")?;
+            let thrown_true: AmountUnit =
+                parsing::try_parse(raw_true).map_err(|error| error.at_line(line_number))?;
+            let (line_number, raw_false) = next_line("If false: throw to monkey ")?;
+            let thrown_false: AmountUnit =
+                parsing::try_parse(raw_false).map_err(|error| error.at_line(line_number))?;
+
+            let monkey_test_logic = Test::new(test_condition, thrown_true, thrown_false);
+
+            Ok(Monkey::new(
+                monkey_id,
+                items_to_start_with
+                    .into_iter()
+                    .map(W::from_amount)
+                    .collect(),
+                operations,
+                monkey_test_logic,
+            ))
+        })
+        .collect()
 }
 
 impl Test {
-    pub fn new(
-        divider: AmountUnit,
-        thrown_if_true: AmountUnit,
-        thrown_if_false: AmountUnit,
-    ) -> Self {
+    fn new(condition: Condition, thrown_if_true: AmountUnit, thrown_if_false: AmountUnit) -> Self {
         Self {
-            divider,
+            condition,
             thrown_if_true,
             thrown_if_false,
         }
     }
 
-    pub fn return_right_value(&self, amount: AmountUnit) -> AmountUnit {
-        if (amount % self.divider) == 0 {
+    fn return_right_value<W: Worry>(&self, amount: &W) -> AmountUnit {
+        if self.condition.holds_for(amount) {
             self.thrown_if_true
         } else {
             self.thrown_if_false
@@ -211,88 +592,297 @@ impl Test {
     }
 }
 
+impl Condition {
+    fn holds_for<W: Worry>(&self, amount: &W) -> bool {
+        match self {
+            Condition::DivisibleBy(divider) => amount.remainder_by(*divider) == 0,
+            Condition::GreaterThan(bound) => amount.compare_to(*bound) == Ordering::Greater,
+            Condition::LessThan(bound) => amount.compare_to(*bound) == Ordering::Less,
+            Condition::Equals(bound) => amount.compare_to(*bound) == Ordering::Equal,
+        }
+    }
+}
+
+impl FromStr for Condition {
+    type Err = OperationParseErr;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const CONDITIONS: [(&str, ConditionFromAmount); 4] = [
+            ("divisible by ", Condition::DivisibleBy),
+            ("greater than ", Condition::GreaterThan),
+            ("less than ", Condition::LessThan),
+            ("equals ", Condition::Equals),
+        ];
+
+        CONDITIONS
+            .iter()
+            .find_map(|(prefix, to_condition)| {
+                s.strip_prefix(prefix)
+                    .map(|amount| (amount.trim().parse(), to_condition))
+            })
+            .ok_or(OperationParseErr {
+                _error_message: "Unknown test condition",
+            })
+            .and_then(|(amount, to_condition)| {
+                amount.map(to_condition).map_err(|_| OperationParseErr {
+                    _error_message: "Amount of test condition is not parseable to number",
+                })
+            })
+    }
+}
+
+/// None if the worry level is divided by zero.
+fn apply_relief<W: Worry>(relief: &Relief<W>, item: W) -> Option<W> {
+    match relief {
+        Relief::None => Some(item),
+        Relief::DivideBy(divisor) => W::checked_calc(Operator::Div, item, W::from_amount(*divisor)),
+        Relief::Custom(to_relief) => Some(to_relief(item)),
+    }
+}
+
 impl FromStr for Operation {
     type Err = OperationParseErr;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut splits = s.split(" ");
-
-        match (splits.next(), splits.next()) {
-            (Some(operator), Some(amount)) => {
-                let mut amount_value = OpAmount::Old;
-                if amount != "old" {
-                    let amount_parsed: AmountUnit =
-                        amount.parse().map_err(|_| OperationParseErr {
-                            _error_message: "Amount for operation is parseable to number",
-                        })?;
-
-                    amount_value = OpAmount::Amount(amount_parsed);
-                }
+        let tokens = tokenize_operation(s)?;
+        let mut position = 0;
+        let parsed = parse_sum(&tokens, &mut position)?;
+
+        if position != tokens.len() {
+            return Err(OperationParseErr {
+                _error_message: "Unexpected token after end of operation",
+            });
+        }
 
-                match operator {
-                    "+" => Ok(Operation::Add(amount_value)),
-                    "*" => Ok(Operation::Mult(amount_value)),
-                    _ => Err(OperationParseErr {
-                        _error_message: "Unkown operator",
-                    }),
+        return Ok(parsed);
+
+        // sum := product (("+" | "-") product)*
+        fn parse_sum(
+            tokens: &[OperationToken],
+            position: &mut usize,
+        ) -> Result<Operation, OperationParseErr> {
+            let mut left = parse_product(tokens, position)?;
+            while let Some(OperationToken::Operator(operator @ (Operator::Add | Operator::Sub))) =
+                tokens.get(*position)
+            {
+                *position += 1;
+                let right = parse_product(tokens, position)?;
+                left = Operation::Binary(Box::new(left), *operator, Box::new(right));
+            }
+
+            Ok(left)
+        }
+
+        // product := single (("*" | "/" | "%") single)*
+        fn parse_product(
+            tokens: &[OperationToken],
+            position: &mut usize,
+        ) -> Result<Operation, OperationParseErr> {
+            let mut left = parse_single(tokens, position)?;
+            while let Some(OperationToken::Operator(
+                operator @ (Operator::Mult | Operator::Div | Operator::Rem),
+            )) = tokens.get(*position)
+            {
+                *position += 1;
+                let right = parse_single(tokens, position)?;
+                left = Operation::Binary(Box::new(left), *operator, Box::new(right));
+            }
+
+            Ok(left)
+        }
+
+        // single := "old" | number | "(" sum ")"
+        fn parse_single(
+            tokens: &[OperationToken],
+            position: &mut usize,
+        ) -> Result<Operation, OperationParseErr> {
+            let next = tokens.get(*position).ok_or(OperationParseErr {
+                _error_message: "Operation ended where old, a number or ( was expected",
+            })?;
+            *position += 1;
+
+            match next {
+                OperationToken::Old => Ok(Operation::Old),
+                OperationToken::Amount(amount) => Ok(Operation::Amount(*amount)),
+                OperationToken::OpenParen => {
+                    let inner = parse_sum(tokens, position)?;
+                    match tokens.get(*position) {
+                        Some(OperationToken::CloseParen) => {
+                            *position += 1;
+                            Ok(inner)
+                        }
+                        _ => Err(OperationParseErr {
+                            _error_message: "Missing closing )",
+                        }),
+                    }
                 }
+                _ => Err(OperationParseErr {
+                    _error_message: "Expected old, a number or (",
+                }),
             }
-            _ => Err(OperationParseErr {
-                _error_message: "No two parst after splitting by whitespace",
-            }),
         }
     }
 }
 
-impl Operation {
-    fn apply_to(&self, item: AmountUnit) -> AmountUnit {
-        return match &self {
-            Operation::Add(amount) => item + Self::get_amount(item, amount),
-            Operation::Mult(amount) => item * Self::get_amount(item, amount),
-        };
-    }
+fn tokenize_operation(to_tokenize: &str) -> Result<Vec<OperationToken>, OperationParseErr> {
+    let mut tokens = Vec::new();
+    let mut chars = to_tokenize.chars().peekable();
+
+    while let Some(next) = chars.next() {
+        let token = match next {
+            '+' => OperationToken::Operator(Operator::Add),
+            '-' => OperationToken::Operator(Operator::Sub),
+            '*' => OperationToken::Operator(Operator::Mult),
+            '/' => OperationToken::Operator(Operator::Div),
+            '%' => OperationToken::Operator(Operator::Rem),
+            '(' => OperationToken::OpenParen,
+            ')' => OperationToken::CloseParen,
+            'o' => {
+                if chars.next() != Some('l') || chars.next() != Some('d') {
+                    return Err(OperationParseErr {
+                        _error_message: "Only old is allowed as a variable",
+                    });
+                }
+                OperationToken::Old
+            }
+            digit if digit.is_ascii_digit() => {
+                let mut number = String::from(digit);
+                while let Some(following) = chars.next_if(|c| c.is_ascii_digit()) {
+                    number.push(following);
+                }
 
-    fn apply_with_mod(&self, item: AmountUnit, modular: usize) -> AmountUnit {
-        return match &self {
-            Operation::Add(amount) => {
-                let (mod_item, mod_amount) = get_modular_left_right(item, &amount, modular);
-                (mod_item + mod_amount) % modular
+                OperationToken::Amount(number.parse().map_err(|_| OperationParseErr {
+                    _error_message: "Amount for operation is not parseable to number",
+                })?)
             }
-            Operation::Mult(amount) => {
-                let (mod_item, mod_amount) = get_modular_left_right(item, &amount, modular);
-                (mod_item * mod_amount) % modular
+            whitespace if whitespace.is_whitespace() => continue,
+            _ => {
+                return Err(OperationParseErr {
+                    _error_message: "Unkown symbol in operation",
+                })
             }
         };
 
-        fn get_modular_left_right(
-            item: usize,
-            amount: &OpAmount,
-            modular: usize,
-        ) -> (usize, usize) {
-            let mod_item = item % modular;
-            let amount = Operation::get_amount(item, amount);
-            let mod_amount = amount % modular;
+        tokens.push(token);
+    }
 
-            (mod_item, mod_amount)
+    Ok(tokens)
+}
+
+impl Worry for AmountUnit {
+    fn from_amount(amount: AmountUnit) -> Self {
+        amount
+    }
+
+    fn checked_calc(operator: Operator, left: Self, right: Self) -> Option<Self> {
+        match operator {
+            Operator::Add => left.checked_add(right),
+            Operator::Sub => left.checked_sub(right),
+            Operator::Mult => left.checked_mul(right),
+            Operator::Div => left.checked_div(right),
+            Operator::Rem => left.checked_rem(right),
         }
     }
 
-    fn get_amount(item: AmountUnit, amount: &OpAmount) -> AmountUnit {
-        match amount {
-            OpAmount::Old => item,
-            OpAmount::Amount(fixed_amount) => *fixed_amount,
+    fn remainder_by(&self, divisor: AmountUnit) -> AmountUnit {
+        self % divisor
+    }
+
+    fn compare_to(&self, amount: AmountUnit) -> Ordering {
+        self.cmp(&amount)
+    }
+}
+
+impl Worry for BigUint {
+    fn from_amount(amount: AmountUnit) -> Self {
+        BigUint::from(amount)
+    }
+
+    fn checked_calc(operator: Operator, left: Self, right: Self) -> Option<Self> {
+        let zero = BigUint::default();
+        match operator {
+            Operator::Add => Some(left + right),
+            Operator::Sub if left >= right => Some(left - right),
+            Operator::Sub => None,
+            Operator::Mult => Some(left * right),
+            Operator::Div if right != zero => Some(left / right),
+            Operator::Rem if right != zero => Some(left % right),
+            Operator::Div | Operator::Rem => None,
         }
     }
+
+    fn remainder_by(&self, divisor: AmountUnit) -> AmountUnit {
+        let remainder = self % divisor;
+        AmountUnit::try_from(&remainder).expect("Remainder is smaller than its divisor")
+    }
+
+    fn compare_to(&self, amount: AmountUnit) -> Ordering {
+        self.cmp(&BigUint::from(amount))
+    }
 }
 
-impl Monkey {
-    fn new(
-        id: AmountUnit,
-        items: VecDeque<AmountUnit>,
-        operation: Operation,
-        test_logic: Test,
-    ) -> Self {
+impl Operation {
+    fn apply_to<W: Worry>(&self, item: &W) -> Option<W> {
+        match self {
+            Operation::Old => Some(item.clone()),
+            Operation::Amount(amount) => Some(W::from_amount(*amount)),
+            Operation::Binary(left, operator, right) => {
+                W::checked_calc(*operator, left.apply_to(item)?, right.apply_to(item)?)
+            }
+        }
+    }
+
+    /// None if a value leaves the range of numbers or the operation is not
+    /// supported under the given modular, see `supports_modular`.
+    fn apply_with_mod(&self, item: AmountUnit, modular: usize) -> Option<AmountUnit> {
+        match self {
+            Operation::Old => item.checked_rem(modular),
+            Operation::Amount(amount) => amount.checked_rem(modular),
+            Operation::Binary(left, operator, right) => {
+                let mod_left = left.apply_with_mod(item, modular)?;
+                let result = match (operator, right.as_ref()) {
+                    (Operator::Add, _) => {
+                        mod_left.checked_add(right.apply_with_mod(item, modular)?)
+                    }
+                    (Operator::Mult, _) => {
+                        mod_left.checked_mul(right.apply_with_mod(item, modular)?)
+                    }
+                    (Operator::Rem, Operation::Amount(divisor)) => mod_left.checked_rem(*divisor),
+                    _ => None,
+                };
+
+                result?.checked_rem(modular)
+            }
+        }
+    }
+
+    /// Division does not survive calculating under a modular. A remainder only
+    /// does if it is taken by a fixed amount which divides the modular.
+    /// Subtraction is left out since a worry level below zero is an overflow
+    /// like in the other modes, which a remainder can not tell.
+    fn supports_modular(&self, modular: usize) -> bool {
+        match self {
+            Operation::Old | Operation::Amount(_) => true,
+            Operation::Binary(left, operator, right) => {
+                let operator_supported = match (operator, right.as_ref()) {
+                    (Operator::Div | Operator::Sub, _) => false,
+                    (Operator::Rem, Operation::Amount(divisor)) => {
+                        *divisor != 0 && modular.is_multiple_of(*divisor)
+                    }
+                    (Operator::Rem, _) => false,
+                    _ => true,
+                };
+
+                operator_supported
+                    && left.supports_modular(modular)
+                    && right.supports_modular(modular)
+            }
+        }
+    }
+}
+
+impl<W: Worry> Monkey<W> {
+    fn new(id: AmountUnit, items: VecDeque<W>, operation: Operation, test_logic: Test) -> Self {
         Self {
-            _id: id,
+            id,
             items,
             operation,
             test_logic,
@@ -305,40 +895,434 @@ impl Monkey {
         self.modular = Some(modular)
     }
 
-    fn give_thrown_item(&mut self, new_item: AmountUnit) {
+    fn give_thrown_item(&mut self, new_item: W) {
         self.items.push_back(new_item);
     }
 
-    fn inpect_next_round(&mut self) -> VecDeque<ItemThrown> {
+    /// None if a worry level left the range of numbers.
+    fn inpect_next_round(&mut self, relief: &Relief<W>) -> Option<VecDeque<ItemThrown<W>>> {
         let to_return = self
             .items
             .iter()
             .map(|next_item| {
                 let worried_level = match self.modular {
                     None => {
-                        let from_operation = self.operation.apply_to(*next_item);
+                        let from_operation = self.operation.apply_to(next_item)?;
 
-                        from_operation / 3
-                    }
-                    Some(m) => {
-                        let from_operation = self.operation.apply_with_mod(*next_item, m);
-
-                        from_operation
+                        apply_relief(relief, from_operation)?
                     }
+                    Some(m) => W::from_amount(
+                        self.operation
+                            .apply_with_mod(next_item.remainder_by(m), m)?,
+                    ),
                 };
 
-                let to_throw_to = self.test_logic.return_right_value(worried_level);
+                let to_throw_to = self.test_logic.return_right_value(&worried_level);
 
-                ItemThrown {
+                Some(ItemThrown {
                     item: worried_level,
                     monkey_thrown_to: to_throw_to,
-                }
+                })
             })
-            .collect();
+            .collect::<Option<VecDeque<ItemThrown<W>>>>()?;
 
         self.number_inspection += self.items.len();
         self.items.clear();
 
-        to_return
+        Some(to_return)
+    }
+}
+
+impl<W: Clone> RoundReport<W> {
+    fn from_monkeys(round: usize, monkeys: &VecDeque<Monkey<W>>) -> Self {
+        Self {
+            round,
+            holdings: monkeys
+                .iter()
+                .map(|monkey| monkey.items.iter().cloned().collect())
+                .collect(),
+            inspections: monkeys
+                .iter()
+                .map(|monkey| monkey.number_inspection)
+                .collect(),
+        }
+    }
+}
+
+impl<W: Display> Display for RoundReport<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "== After round {} ==", self.round)?;
+        for (id, items) in self.holdings.iter().enumerate() {
+            let listed: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+            writeln!(f, "Monkey {}: {}", id, listed.join(", "))?;
+        }
+        for (id, number) in self.inspections.iter().enumerate() {
+            writeln!(f, "Monkey {} inspected items {} times.", id, number)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for MonkeyGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonkeyGraphError::IdNotAtPosition { block, id } => write!(
+                f,
+                "Monkey block {}: has id {} but must have id {}",
+                block, id, block
+            ),
+            MonkeyGraphError::UnknownTarget { block, target } => write!(
+                f,
+                "Monkey block {}: throws to monkey {} which does not exist",
+                block, target
+            ),
+            MonkeyGraphError::ThrowsToItself { block } => {
+                write!(f, "Monkey block {}: throws to itself", block)
+            }
+            MonkeyGraphError::DivisorIsZero { block } => {
+                write!(
+                    f,
+                    "Monkey block {}: test checks divisibility by zero",
+                    block
+                )
+            }
+        }
+    }
+}
+
+impl Display for WorryOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Worry level left the range of numbers at monkey {} in round {}",
+            self.monkey, self.round
+        )
+    }
+}
+
+impl From<ParseError> for MonkeyError {
+    fn from(error: ParseError) -> Self {
+        MonkeyError::InvalidNotes(error)
+    }
+}
+
+impl From<WorryOverflow> for MonkeyError {
+    fn from(overflow: WorryOverflow) -> Self {
+        MonkeyError::Overflow(overflow)
+    }
+}
+
+impl Display for MonkeyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MonkeyError::InvalidNotes(error) => write!(f, "{}", error),
+            MonkeyError::InvalidGraph(errors) => {
+                let listed: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "Invalid monkey notes:\n{}", listed.join("\n"))
+            }
+            MonkeyError::Overflow(overflow) => write!(f, "{}", overflow),
+            MonkeyError::NoCommonModular { monkey } => write!(
+                f,
+                "Monkey {} can not be calculated under a common modular",
+                monkey
+            ),
+            MonkeyError::CommonModularTooLarge => {
+                write!(f, "Common modular of all test dividers is too large")
+            }
+            MonkeyError::ReportRoundOutOfRange { round, last_round } => write!(
+                f,
+                "Round {} can not be reported, only rounds 1 to {} are played",
+                round, last_round
+            ),
+            MonkeyError::BusinessOverflow => {
+                write!(f, "Monkey business is too large to be calculated")
+            }
+            MonkeyError::InspectionOverflow => {
+                write!(f, "Inspections are too many to be counted")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    const EXAMPLE: &str = "\
+Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+
+Monkey 1:
+  Starting items: 54, 65, 75, 74
+  Operation: new = old + 6
+  Test: divisible by 19
+    If true: throw to monkey 2
+    If false: throw to monkey 0
+
+Monkey 2:
+  Starting items: 79, 60, 97
+  Operation: new = old * old
+  Test: divisible by 13
+    If true: throw to monkey 1
+    If false: throw to monkey 3
+
+Monkey 3:
+  Starting items: 74
+  Operation: new = old + 3
+  Test: divisible by 17
+    If true: throw to monkey 0
+    If false: throw to monkey 1
+";
+
+    #[test]
+    fn test_operation_precedence_and_parentheses() {
+        // Set up
+        let plain: Operation = "old * old + 3".parse().unwrap();
+        let grouped: Operation = "old * (old + 3) - 10 % 4".parse().unwrap();
+
+        // Act
+        let actual = (plain.apply_to(&5usize), grouped.apply_to(&5usize));
+
+        // Assert
+        assert_eq!((Some(28), Some(38)), actual);
+    }
+
+    #[test]
+    fn test_operation_with_mod_matches_exact() {
+        // Set up
+        const MODULAR: usize = 96577;
+        let operation: Operation = "(old * old + 3) % 7 * 19 + old % 17 + 5".parse().unwrap();
+        let subtracting: Operation = "old - 5".parse().unwrap();
+
+        // Act
+        let exact = operation.apply_to(&1234usize).unwrap() % MODULAR;
+        let with_mod = operation.apply_with_mod(1234, MODULAR * 7 * 17).unwrap();
+
+        // Assert
+        assert!(operation.supports_modular(MODULAR * 7 * 17));
+        assert!(!operation.supports_modular(MODULAR));
+        assert_eq!(exact, with_mod % MODULAR);
+        assert!(!subtracting.supports_modular(MODULAR));
+        assert_eq!(None, subtracting.apply_to(&3usize));
+        assert_eq!(None, subtracting.apply_with_mod(3, MODULAR));
+    }
+
+    #[test]
+    fn test_checked_reports_overflow() {
+        // Set up
+        let input = "\
+Monkey 0:
+  Starting items: 10
+  Operation: new = old * old
+  Test: divisible by 2
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 3
+  Operation: new = old * old
+  Test: divisible by 3
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+
+        // Act
+        let checked = calc_top_inspecting_number_checked(input, 2, 6, &Relief::None);
+        let exact = calc_top_inspecting_number_exact(input, 2, 6, &Relief::None);
+
+        // Assert
+        assert_eq!(
+            Err(MonkeyError::Overflow(WorryOverflow {
+                monkey: 0,
+                round: 3
+            })),
+            checked
+        );
+        assert_eq!(Ok(132), exact);
+    }
+
+    #[test]
+    fn test_business_overflow() {
+        // Act
+        let simulated = calc_top_inspecting_number_modular(EXAMPLE, 4, 50_000);
+        let fast_forward =
+            calc_top_inspecting_number_fast_forward(EXAMPLE, 2, 18_000_000_000_000_000_000);
+
+        // Assert
+        assert_eq!(Err(MonkeyError::BusinessOverflow), simulated);
+        assert_eq!(Err(MonkeyError::BusinessOverflow), fast_forward);
+    }
+
+    #[test]
+    fn test_fast_forward_matches_simulation() {
+        // Act
+        let simulated = calc_top_inspecting_number_modular(EXAMPLE, 2, 10_000);
+        let fast_forward = calc_top_inspecting_number_fast_forward(EXAMPLE, 2, 10_000);
+
+        // Assert
+        assert_eq!(Ok(2713310158), simulated);
+        assert_eq!(Ok(2713310158), fast_forward);
+        assert_eq!(
+            Ok(6),
+            calc_top_inspecting_number_fast_forward(EXAMPLE, 1, 1)
+        );
+        assert_eq!(
+            Ok(93_917_763_157_894_736_825),
+            calc_top_inspecting_number_fast_forward(EXAMPLE, 1, 18_000_000_000_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_fast_forward_needs_common_modular() {
+        // Set up
+        let input = "\
+Monkey 0:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: divisible by 2
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 2
+  Operation: new = old + 1
+  Test: greater than 5
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+
+        // Act
+        let actual = calc_top_inspecting_number_fast_forward(input, 2, 1_000);
+
+        // Assert
+        assert_eq!(Err(MonkeyError::NoCommonModular { monkey: 1 }), actual);
+    }
+
+    #[test]
+    fn test_parse_conditions() {
+        // Act
+        let actual: Vec<Option<Condition>> = [
+            "divisible by 23",
+            "greater than 50",
+            "less than 3",
+            "equals 7",
+            "odd",
+        ]
+        .iter()
+        .map(|condition| condition.parse().ok())
+        .collect();
+
+        // Assert
+        assert_eq!(Some(Condition::DivisibleBy(23)), actual[0]);
+        assert_eq!(Some(Condition::GreaterThan(50)), actual[1]);
+        assert_eq!(Some(Condition::LessThan(3)), actual[2]);
+        assert_eq!(Some(Condition::Equals(7)), actual[3]);
+        assert_eq!(None, actual[4]);
+    }
+
+    #[test]
+    fn test_custom_relief_and_comparing_tests() {
+        // Set up
+        let input = "\
+Monkey 0:
+  Starting items: 10
+  Operation: new = old * 2
+  Test: greater than 15
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 1
+  Operation: new = old + 1
+  Test: equals 2
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+        let relief: Relief<AmountUnit> = Relief::Custom(|worry| worry - 1);
+
+        // Act
+        let actual = get_round_reports(input, &[2], 2, &relief).unwrap();
+
+        // Assert
+        assert_eq!(vec![vec![1, 37], vec![]], actual[0].holdings);
+        assert_eq!(vec![3, 4], actual[0].inspections);
+    }
+
+    #[test]
+    fn test_validate_notes() {
+        // Set up
+        let input = "\
+Monkey 0:
+  Starting items: 1
+  Operation: new = old
+  Test: divisible by 0
+    If true: throw to monkey 0
+    If false: throw to monkey 2
+
+Monkey 3:
+  Starting items: 1
+  Operation: new = old
+  Test: divisible by 2
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+";
+
+        // Act
+        let actual = validate_notes(input);
+        let simulated = calc_top_inspecting_number_checked(input, 2, 20, &Relief::None);
+        let unparsable =
+            validate_notes("Monkey 0:\n  Starting items: 1\n  Operation: new = old ^ 2\n");
+
+        // Assert
+        assert_eq!(
+            Err(MonkeyError::InvalidGraph(vec![
+                MonkeyGraphError::DivisorIsZero { block: 0 },
+                MonkeyGraphError::ThrowsToItself { block: 0 },
+                MonkeyGraphError::UnknownTarget {
+                    block: 0,
+                    target: 2
+                },
+                MonkeyGraphError::IdNotAtPosition { block: 1, id: 3 },
+            ])),
+            actual
+        );
+        assert!(matches!(simulated, Err(MonkeyError::InvalidGraph(errors)) if errors.len() == 4));
+        assert!(
+            matches!(unparsable, Err(MonkeyError::InvalidNotes(error)) if error.line == Some(3))
+        );
+    }
+
+    #[test]
+    fn test_round_reports() {
+        // Act
+        let with_relief = get_round_reports(EXAMPLE, &[1], 20, &Relief::DivideBy(3)).unwrap();
+        let no_relief = get_round_reports(EXAMPLE, &[1, 20], 20, &Relief::None).unwrap();
+        let after_last = get_round_reports(EXAMPLE, &[21], 20, &Relief::None);
+
+        // Assert
+        assert_eq!(
+            vec![
+                vec![20, 23, 27, 26],
+                vec![2080, 25, 167, 207, 401, 1046],
+                vec![],
+                vec![]
+            ],
+            with_relief[0].holdings
+        );
+        assert_eq!(vec![2, 4, 3, 6], no_relief[0].inspections);
+        assert_eq!(vec![99, 97, 8, 103], no_relief[1].inspections);
+        assert_eq!(
+            Err(MonkeyError::ReportRoundOutOfRange {
+                round: 21,
+                last_round: 20
+            }),
+            after_last
+        );
     }
 }
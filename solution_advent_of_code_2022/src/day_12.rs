@@ -62,9 +62,7 @@ pub fn calc_path_with_fewest_steps(input: &str) -> usize {
 pub fn calc_path_from_any_a_fewest_steps(input: &str) -> ((usize, usize), usize) {
     let mut mountains = parse_input(input);
 
-    let new_start = mountains.end;
-    mountains.end = mountains.start;
-    mountains.start = new_start;
+    std::mem::swap(&mut mountains.end, &mut mountains.start);
 
     let paths = build_path_map(&mountains, |adjacant, current| {
         let too_low = current - 1;
@@ -106,13 +104,13 @@ where
         let adjacant = get_adjacant_coord(next_coord.0, max_y, max_x);
 
         let currend_coord = next_coord.0;
-        let current_height = currend_coord.get_cell_from(&grid);
+        let current_height = currend_coord.get_cell_from(grid);
 
         let current_node = next_coord.0;
         let current_cost = next_coord.1 + 1;
 
         for current_adjacant in adjacant.iter() {
-            let adjacant_height = current_adjacant.get_cell_from(&grid);
+            let adjacant_height = current_adjacant.get_cell_from(grid);
 
             if skip_predicate(adjacant_height, current_height) {
                 continue;
@@ -122,7 +120,7 @@ where
                 open.push_back((*current_adjacant, current_cost));
             }
 
-            match path_map.get_mut(&current_adjacant) {
+            match path_map.get_mut(current_adjacant) {
                 Some(found) => {
                     if found.cost > current_cost {
                         found.cost = current_cost;
@@ -109,16 +109,16 @@ fn compare_pair_queies(pair: PairPacket) -> bool {
 }
 
 fn parse_input(input: &str) -> Vec<PairPacket> {
-    let chunks = parsing::split_chunks_where(input, |line| line.is_empty());
+    let chunks = parsing::split_blocks(input);
 
     return chunks
         .into_iter()
         .enumerate()
         .map(
-            |left_right| match (left_right.1.get(0), left_right.1.get(1)) {
+            |left_right| match (left_right.1.lines.first(), left_right.1.lines.get(1)) {
                 (Some(&left), Some(&right)) => {
-                    let left_parsed = return_nested_packet(&left);
-                    let right_parsed = return_nested_packet(&right);
+                    let left_parsed = return_nested_packet(left);
+                    let right_parsed = return_nested_packet(right);
                     PairPacket::new(left_parsed, right_parsed)
                 }
                 _ => panic!("No 2 lines for the left and right part of the pair packet."),
@@ -154,7 +154,7 @@ fn parse_input(input: &str) -> Vec<PairPacket> {
                         }
                     }
                 }
-                ']' => return (Box::new(Packet::List(Vec::new())), index),
+                ']' => (Box::new(Packet::List(Vec::new())), index),
                 _digit => {
                     let mut number_str = String::new();
 
@@ -165,7 +165,7 @@ fn parse_input(input: &str) -> Vec<PairPacket> {
                     }
                     let number = number_str.parse().unwrap();
 
-                    return (Box::new(Packet::Integer(number)), index);
+                    (Box::new(Packet::Integer(number)), index)
                 }
             }
         }
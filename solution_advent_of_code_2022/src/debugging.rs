@@ -17,7 +17,7 @@ macro_rules! print_var {
         println!("{}: {}", $l, $c);
     };
 }
-pub fn grid_to_string<T>(grid: &Vec<Vec<T>>, sep: &str) -> String
+pub fn grid_to_string<T>(grid: &[Vec<T>], sep: &str) -> String
 where
     T: Display,
 {
@@ -48,7 +48,7 @@ where
 
     let mut write_to = String::with_capacity(max_len * height * width + (width * sep.len()));
 
-    write_one_line(&mut write_to, max_len, &columns_indices, &sep);
+    write_one_line(&mut write_to, max_len, &columns_indices, sep);
     write_to.push('\n');
 
     let banner = "=".repeat(write_to.len() - 1);
@@ -56,7 +56,7 @@ where
     write_to.push('\n');
 
     for (index, line) in buffer.into_iter().enumerate() {
-        write_one_line(&mut write_to, max_len, &line, &sep);
+        write_one_line(&mut write_to, max_len, &line, sep);
 
         let end_of_line = format!("+{}\n", index);
         write_to.push_str(&end_of_line);
@@ -78,7 +78,7 @@ where
     }
 }
 
-pub fn print_grid<T>(grid: &Vec<Vec<T>>, sep: &str)
+pub fn print_grid<T>(grid: &[Vec<T>], sep: &str)
 where
     T: Display,
 {
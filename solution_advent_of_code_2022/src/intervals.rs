@@ -0,0 +1,240 @@
+use crate::advent_math::Integer;
+use std::fmt::Display;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalError {
+    StartAfterEnd,
+}
+
+/// Whole numbers from start to end, both included, like `RangeInclusive` but
+/// never empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Interval<T> {
+    start: T,
+    end: T,
+}
+
+/// Disjoint intervals sorted by their start. Overlapping or touching
+/// intervals are merged when inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IntervalSet<T> {
+    intervals: Vec<Interval<T>>,
+}
+
+impl<T: Integer> Interval<T> {
+    pub fn new(start: T, end: T) -> Result<Self, IntervalError> {
+        if start > end {
+            return Err(IntervalError::StartAfterEnd);
+        }
+
+        Ok(Self { start, end })
+    }
+
+    pub fn single(value: T) -> Self {
+        Self {
+            start: value,
+            end: value,
+        }
+    }
+
+    pub fn start(&self) -> T {
+        self.start
+    }
+
+    pub fn end(&self) -> T {
+        self.end
+    }
+
+    /// Number of values in the interval. None if it does not fit into `T`
+    /// like for 0 to 255 as u8.
+    pub fn length(&self) -> Option<T> {
+        self.end.checked_sub(self.start)?.checked_add(T::ONE)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.start <= value && value <= self.end
+    }
+
+    pub fn contains_interval(&self, other: &Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        Self::new(self.start.max(other.start), self.end.min(other.end)).ok()
+    }
+
+    /// One interval covering both. None if there is a gap between them,
+    /// touching intervals like 1-3 and 4-6 are joined.
+    pub fn union(&self, other: &Self) -> Option<Self> {
+        if !self.overlaps(other) && !self.touches(other) {
+            return None;
+        }
+
+        Some(Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        })
+    }
+
+    /// Values of this interval which are not in the other one, at most two
+    /// intervals.
+    pub fn difference(&self, other: &Self) -> Vec<Self> {
+        if !self.overlaps(other) {
+            return vec![*self];
+        }
+
+        let before = other
+            .start
+            .checked_sub(T::ONE)
+            .and_then(|end| Self::new(self.start, end).ok());
+        let after = other
+            .end
+            .checked_add(T::ONE)
+            .and_then(|start| Self::new(start, self.end).ok());
+
+        before.into_iter().chain(after).collect()
+    }
+
+    /// True if one ends right before the other starts.
+    fn touches(&self, other: &Self) -> bool {
+        let right_after = |end: T, start: T| end.checked_add(T::ONE) == Some(start);
+        right_after(self.end, other.start) || right_after(other.end, self.start)
+    }
+}
+
+impl<T: Integer> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    pub fn intervals(&self) -> &[Interval<T>] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    pub fn insert(&mut self, to_insert: Interval<T>) {
+        let mut merged = to_insert;
+        let mut kept = Vec::with_capacity(self.intervals.len() + 1);
+        for interval in self.intervals.drain(..) {
+            match merged.union(&interval) {
+                Some(joined) => merged = joined,
+                None => kept.push(interval),
+            }
+        }
+
+        let position = kept.partition_point(|interval| interval.start < merged.start);
+        kept.insert(position, merged);
+        self.intervals = kept;
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        self.intervals
+            .iter()
+            .any(|interval| interval.contains(value))
+    }
+
+    /// Number of all values in the set. None if it does not fit into `T`.
+    pub fn length(&self) -> Option<T> {
+        self.intervals.iter().try_fold(T::ZERO, |total, interval| {
+            total.checked_add(interval.length()?)
+        })
+    }
+
+    /// Values which are in both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intersection = Self::new();
+        for left in &self.intervals {
+            for right in &other.intervals {
+                if let Some(common) = left.intersection(right) {
+                    intersection.insert(common);
+                }
+            }
+        }
+
+        intersection
+    }
+}
+
+impl<T: Integer> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Integer> FromIterator<Interval<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Interval<T>>>(iter: I) -> Self {
+        let mut set = Self::new();
+        iter.into_iter().for_each(|interval| set.insert(interval));
+        set
+    }
+}
+
+impl Display for IntervalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntervalError::StartAfterEnd => write!(f, "Start of an interval is after its end"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    fn interval(start: u32, end: u32) -> Interval<u32> {
+        Interval::new(start, end).unwrap()
+    }
+
+    #[test]
+    fn test_interval_operations() {
+        // Set up
+        let left = interval(2, 6);
+        let right = interval(4, 8);
+
+        // Act
+        let intersection = left.intersection(&right);
+        let union = left.union(&right);
+        let difference = left.difference(&interval(3, 4));
+
+        // Assert
+        assert_eq!(Some(interval(4, 6)), intersection);
+        assert_eq!(Some(interval(2, 8)), union);
+        assert_eq!(vec![interval(2, 2), interval(5, 6)], difference);
+        assert_eq!(None, interval(1, 2).union(&interval(4, 5)));
+        assert_eq!(None, Interval::new(0u8, u8::MAX).unwrap().length());
+        assert_eq!(Err(IntervalError::StartAfterEnd), Interval::new(3, 2));
+    }
+
+    #[test]
+    fn test_interval_set_merges() {
+        // Act
+        let set: IntervalSet<u32> = [
+            interval(6, 8),
+            interval(1, 2),
+            interval(3, 4),
+            interval(10, 12),
+        ]
+        .into_iter()
+        .collect();
+        let mut with_bridge = set.clone();
+        with_bridge.insert(interval(5, 9));
+
+        // Assert
+        assert_eq!(
+            &[interval(1, 4), interval(6, 8), interval(10, 12)],
+            set.intervals()
+        );
+        assert_eq!(Some(10), set.length());
+        assert_eq!(&[interval(1, 12)], with_bridge.intervals());
+    }
+}
@@ -1,3 +1,4 @@
+pub mod advent_font;
 pub mod advent_math;
 pub mod challenge_args;
 pub mod day_01;
@@ -14,5 +15,6 @@ pub mod day_11;
 pub mod day_12;
 pub mod day_13;
 pub mod debugging;
+pub mod intervals;
 pub mod parsing;
 pub mod sequences;
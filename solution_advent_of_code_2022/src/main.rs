@@ -1,16 +1,21 @@
 use clap::Parser;
 use solution_advent_of_code_2022::{
-    challenge_args::ChallangeArgs,
+    challenge_args::{ChallangeArgs, CrtFormat, Priorities},
     day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, 
     day_11, day_12, day_13
 };
 
+use solution_advent_of_code_2022::parsing::StreamError;
 use std::fs;
-use std::io;
+use std::io::{self, BufRead, BufReader, Read};
+
+/// Given as path together with input_as_path to read the input from stdin.
+const STDIN_PATH: &str = "-";
 
 const TASK_ONE: u32 = 1;
 const TASK_TWO: u32 = 2;
 const TASK_THREE: u32 = 3;
+const TASK_FOUR: u32 = 4;
 
 const DAY_01: u32 = 1;
 const DAY_02: u32 = 2;
@@ -29,59 +34,87 @@ const DAY_13: u32 = 13;
 fn main() {
     let mut args: ChallangeArgs = ChallangeArgs::parse();
     read_file_if_needed(&mut args).expect("Could read not input from file.");
+    print_trace_if_needed(&args);
     solve_for_certain_day(&args);
 }
 
+fn print_trace_if_needed(args: &ChallangeArgs) {
+    if !args.trace {
+        return;
+    }
+
+    match args.day {
+        DAY_10 => match day_10::get_trace_with(&args.input, &day_10::InstructionSet::extended(), args.max_cycles) {
+            Ok(trace) => println!("{}", trace),
+            Err(error) => eprintln!("Could not trace program: {}", error),
+        },
+        no_trace_day => eprintln!("No trace available for day with number: {no_trace_day}"),
+    }
+}
+
 fn solve_for_certain_day(args: &ChallangeArgs) {
     match args.day {
         DAY_01 => match args.task {
             TASK_ONE => println!(
                 "Most calories: {}",
-                day_01::get_top_calorie(&args).expect("Error in parsing input")
+                expect_streamed(day_01::get_total_cal_top_from_reader(open_input(args), 1))
             ),
             TASK_TWO => println!(
                 "Calories of top 3 evles: {}",
-                day_01::get_total_cal_top(args, 3).expect("Error in parsing input")
+                expect_streamed(day_01::get_total_cal_top_from_reader(open_input(args), 3))
             ),
             invalid_task => abort_for_invalid_task(invalid_task),
         },
         DAY_02 => match args.task {
             TASK_ONE => {
-                let total = day_02::calc_score_of_strat(&args.input);
+                let total = expect_streamed(day_02::calc_score_of_strat_from_reader(open_input(args), &get_game_rules(args)));
                 println!("The score following the strategy is: {}", total);
             }
             TASK_TWO => {
-                let total = day_02::calc_score_outcome_strat(&args.input);
+                let total = expect_streamed(day_02::calc_score_outcome_strat_from_reader(open_input(args), &get_game_rules(args)));
                 println!("The score following the outcome strategy is: {}", total);
             }
+            TASK_THREE => {
+                let report = expect_streamed(day_02::analyse_strategy_from_reader(open_input(args), &get_game_rules(args)));
+                println!("{}", report);
+            }
             invalid_task => abort_for_invalid_task(invalid_task),
         },
         DAY_03 => match args.task {
-            TASK_ONE => {
-                let total = day_03::get_total_prio_of_dups(&args.input);
-                println!("The total of priotities of the duplicates in the rucksacks: {total}");
-            }
-            TASK_TWO => {
-                let total = day_03::get_total_prio_of_group_badges(&args.input);
-                println!("The total of prios of group badges: {total}");
-            }
+            TASK_ONE => match day_03::calc_prio_of_duplicates_with(&args.input, args.compartments, get_priority_scheme(args).as_ref()) {
+                Ok(total) => println!("The total of priotities of the duplicates in the rucksacks: {total}"),
+                Err(error) => eprintln!("{}", error),
+            },
+            TASK_TWO => match day_03::calc_prio_of_group_badges_with(&args.input, args.group_size, get_priority_scheme(args).as_ref()) {
+                Ok(total) => println!("The total of prios of group badges: {total}"),
+                Err(error) => eprintln!("{}", error),
+            },
             invalid_task => abort_for_invalid_task(invalid_task),
         },
         DAY_04 => match args.task {
             TASK_ONE => {
-                let number_fully_contained = day_04::calc_number_contained_assignment(&args.input);
+                let number_fully_contained = expect_streamed(day_04::calc_number_contained_assignment_from_reader(open_input(args)));
                 println!(
                     "Number of section fully contained by another: {}",
                     number_fully_contained
                 );
             }
             TASK_TWO => {
-                let number_any_common_section = day_04::calc_for_any_common_section(&args.input);
+                let number_any_common_section = expect_streamed(day_04::calc_for_any_common_section_from_reader(open_input(args)));
                 println!(
                     "Number lines with any commond section: {}",
                     number_any_common_section
                 );
             }
+            TASK_THREE => {
+                let covered = expect_streamed(day_04::calc_sections_covered_from_reader(open_input(args)));
+                println!("Number of sections covered by all elves together: {}", covered);
+            }
+            TASK_FOUR => {
+                let stats = expect_streamed(day_04::get_assignment_stats_from_reader(open_input(args)));
+                println!("{}\n", stats);
+                print!("{}", day_04::render_pairs(&stats.pairs));
+            }
             invalid_task => abort_for_invalid_task(invalid_task),
         },
         DAY_05 => match args.task {
@@ -97,15 +130,50 @@ fn solve_for_certain_day(args: &ChallangeArgs) {
         },
         DAY_06 => match args.task {
             TASK_ONE => {
-                let start_of_first_packe = day_06::get_end_of_first_packet_start(&args.input, 4);
-                println!("Start marker of 1. packet ends at {}", start_of_first_packe);
+                match day_06::get_end_of_first_packet_start_from_reader(open_input(args), args.marker_size.unwrap_or(4)) {
+                    Ok(start_of_first_packe) => println!("Start marker of 1. packet ends at {}", start_of_first_packe),
+                    Err(error) => eprintln!("{}", error),
+                }
             }
             TASK_TWO => {
-                let start_of_first_message = day_06::get_end_of_first_packet_start(&args.input, 14);
-                println!(
-                    "Start marker of 1. message ends at {}",
-                    start_of_first_message
-                );
+                match day_06::get_end_of_first_packet_start_from_reader(open_input(args), args.marker_size.unwrap_or(14)) {
+                    Ok(start_of_first_message) => println!(
+                        "Start marker of 1. message ends at {}",
+                        start_of_first_message
+                    ),
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
+            TASK_THREE => {
+                let size_marker = args.marker_size.unwrap_or(4);
+                match day_06::get_all_marker_ends_from_reader(open_input(args), size_marker) {
+                    Ok(marker_ends) => {
+                        for marker_end in marker_ends {
+                            match marker_end {
+                                Ok(end) => println!("Marker of size {} ends at {}", size_marker, end),
+                                Err(error) => eprintln!("{}", error),
+                            }
+                        }
+                    }
+                    Err(error) => eprintln!("{}", error),
+                }
+            }
+            TASK_FOUR => {
+                let config = day_06::FrameConfig {
+                    start_marker_size: args.marker_size.unwrap_or(4),
+                    delimiter_marker_size: args.delimiter_marker_size,
+                };
+                match day_06::decode_frames_from_reader(open_input(args), config) {
+                    Ok(frames) => {
+                        for frame in frames {
+                            match frame {
+                                Ok(frame) => println!("{:>8}: {}", frame.offset, String::from_utf8_lossy(&frame.payload)),
+                                Err(error) => eprintln!("{}", error),
+                            }
+                        }
+                    }
+                    Err(error) => eprintln!("{}", error),
+                }
             }
             invalid_task => abort_for_invalid_task(invalid_task),
         },
@@ -168,30 +236,71 @@ fn solve_for_certain_day(args: &ChallangeArgs) {
                 const CYCLE_OFFSET: usize = 20;
                 const CYCLE_STEPS: usize = 40;
                 const MAX_CYCLE: usize = 220;
-                let total_signal_strength = day_10::get_signal_strength_up_to(&args.input, MAX_CYCLE, CYCLE_STEPS, CYCLE_OFFSET);
-                println!("Signal strength at cycle {} in {} steps and starting with offset {}: {}", 
-                         MAX_CYCLE, 
-                         CYCLE_STEPS, 
-                         CYCLE_OFFSET, 
-                         total_signal_strength);
+                match day_10::get_signal_strength_with(&args.input, &day_10::InstructionSet::extended(), MAX_CYCLE, CYCLE_STEPS, CYCLE_OFFSET) {
+                    Ok(total_signal_strength) => println!("Signal strength at cycle {} in {} steps and starting with offset {}: {}", 
+                             MAX_CYCLE, 
+                             CYCLE_STEPS, 
+                             CYCLE_OFFSET, 
+                             total_signal_strength),
+                    Err(error) => eprintln!("Could not run program: {}", error),
+                }
             }
-            TASK_TWO => {
-                const CRT_HEIGHT: usize = 6;
-                const CRT_WIDTH: usize = 40;
+            TASK_TWO => print_crt_screen(args),
+            TASK_THREE => {
+                let config = get_crt_config(args);
+                let pixels = day_10::parse_pixels(&args.input, args.lit_pixel);
 
-                let drawing = day_10::get_drawing(&args.input, CRT_HEIGHT, CRT_WIDTH);
-                println!("{}", drawing);
-            },
+                match day_10::assemble_drawing(&pixels, &config) {
+                    Ok(program) => println!("{}", program),
+                    Err(error) => eprintln!("Could not create program for drawing: {:?}", error),
+                }
+            }
+            TASK_FOUR => {
+                match day_10::annotate_program_with(&args.input, &day_10::InstructionSet::extended(), args.max_cycles) {
+                    Ok(annotated) => println!("{}", annotated),
+                    Err(error) => eprintln!("Could not annotate program: {}", error),
+                }
+            }
             invalid_task => abort_for_invalid_task(invalid_task),
         },
         DAY_11 => match args.task {
             TASK_ONE => {
-                let monkey_business = day_11::calc_top_2_inspecting_number(&args.input);
-                println!("Monkey business of the 2 top most active monkeys: {}", monkey_business);
+                let relief = day_11::Relief::DivideBy(args.relief_divisor);
+                let rounds = args.rounds.unwrap_or(20);
+                print_monkey_reports(args, rounds, &relief);
+                match day_11::calc_top_inspecting_number_checked(&args.input, args.top, rounds, &relief) {
+                    Ok(monkey_business) => println!("Monkey business of the {} top most active monkeys: {}", args.top, monkey_business),
+                    Err(error) => eprintln!("{}", error),
+                }
             }
             TASK_TWO => {
-                let monkey_business = day_11::calc_top_2_inspecting_number_no_relief(&args.input);
-                println!("Monkey business of the 2 top most active monkeys without relief: {}", monkey_business);
+                let rounds = args.rounds.unwrap_or(10_000);
+                print_monkey_reports(args, rounds, &day_11::Relief::None);
+                match day_11::calc_top_inspecting_number_no_relief(&args.input, args.top, rounds) {
+                    Ok(monkey_business) => println!("Monkey business of the {} top most active monkeys without relief: {}", args.top, monkey_business),
+                    Err(error) => eprintln!("{}", error),
+                }
+            },
+            TASK_THREE => {
+                let rounds = args.rounds.unwrap_or(20);
+                let exact = day_11::calc_top_inspecting_number_exact(&args.input, args.top, rounds, &day_11::Relief::None);
+                let modular = day_11::calc_top_inspecting_number_modular(&args.input, args.top, rounds);
+                let checked = day_11::calc_top_inspecting_number_checked(&args.input, args.top, rounds, &day_11::Relief::None);
+
+                println!("Monkey business after {} rounds without relief", rounds);
+                for (mode, result) in [("exact", exact), ("modular", modular), ("checked", checked)] {
+                    match result {
+                        Ok(monkey_business) => println!("{}: {}", mode, monkey_business),
+                        Err(error) => println!("{}: {}", mode, error),
+                    }
+                }
+            },
+            TASK_FOUR => {
+                let rounds = args.rounds.unwrap_or(1_000_000_000);
+                match day_11::calc_top_inspecting_number_fast_forward(&args.input, args.top, rounds) {
+                    Ok(monkey_business) => println!("Monkey business after {} rounds without relief: {}", rounds, monkey_business),
+                    Err(error) => eprintln!("{}", error),
+                }
             },
             invalid_task => abort_for_invalid_task(invalid_task),
         },
@@ -221,17 +330,98 @@ fn solve_for_certain_day(args: &ChallangeArgs) {
     }
 }
 
+fn get_crt_config(args: &ChallangeArgs) -> day_10::CrtConfig {
+    day_10::CrtConfig {
+        height: args.crt_height,
+        width: args.crt_width,
+        sprite_width: args.sprite_width,
+    }
+}
+
+fn print_crt_screen(args: &ChallangeArgs) {
+    let config = get_crt_config(args);
+    let pixels = match day_10::get_pixels_with(&args.input, &day_10::InstructionSet::extended(), &config) {
+        Ok(pixels) => pixels,
+        Err(error) => return eprintln!("Could not run program: {}", error),
+    };
+
+    let drawing = match args.crt_format {
+        CrtFormat::Text => day_10::render_text(&pixels, args.lit_pixel, args.dark_pixel),
+        CrtFormat::Blocks => day_10::render_blocks(&pixels),
+        CrtFormat::Pbm => return print!("{}", day_10::render_pbm(&pixels)),
+        CrtFormat::Pgm => return print!("{}", day_10::render_pgm(&pixels)),
+    };
+    println!("{}", drawing);
+
+    match day_10::read_letters(&pixels) {
+        Ok(letters) => println!("Letters on the screen: {}", letters),
+        Err(error) => eprintln!("Could not read letters from the screen: {}", error),
+    }
+}
+
+fn get_priority_scheme(args: &ChallangeArgs) -> Box<dyn day_03::PriorityScheme> {
+    match (&args.priority_table, args.priorities) {
+        (Some(table), _) => Box::new(day_03::TablePriorities::new(table).unwrap_or_else(|error| panic!("Invalid priority table: {}", error))),
+        (None, Priorities::Letters) => Box::new(day_03::LetterPriorities),
+        (None, Priorities::Digits) => Box::new(day_03::DigitPriorities),
+    }
+}
+
+fn get_game_rules(args: &ChallangeArgs) -> day_02::GameRules {
+    match &args.rules {
+        Some(path) => {
+            let config = fs::read_to_string(path).expect("Could not read rules from file.");
+            day_02::GameRules::from_config(&config).unwrap_or_else(|error| panic!("Invalid rules: {}", error))
+        }
+        None => day_02::GameRules::puzzle(),
+    }
+}
+
+fn print_monkey_reports(args: &ChallangeArgs, rounds: usize, relief: &day_11::Relief<usize>) {
+    match day_11::get_round_reports(&args.input, &args.report_rounds, rounds, relief) {
+        Ok(reports) => reports.iter().for_each(|report| println!("{}", report)),
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
 fn read_file_if_needed(args: &mut ChallangeArgs) -> Result<(), io::Error> {
-    if !args.input_as_path {
+    if !args.input_as_path || is_streamed(args) {
         return Ok(());
     }
 
-    let file_content = fs::read_to_string(&args.input)?;
+    let file_content = if args.input == STDIN_PATH {
+        let mut from_stdin = String::new();
+        io::stdin().read_to_string(&mut from_stdin)?;
+        from_stdin
+    } else {
+        fs::read_to_string(&args.input)?
+    };
     args.input = file_content;
 
     Ok(())
 }
 
+/// Line oriented days are solved in one pass over a reader, so a file or
+/// stdin is never loaded into memory as a whole.
+fn is_streamed(args: &ChallangeArgs) -> bool {
+    matches!(args.day, DAY_01 | DAY_02 | DAY_04 | DAY_06)
+}
+
+fn open_input(args: &ChallangeArgs) -> Box<dyn BufRead + '_> {
+    if !args.input_as_path {
+        Box::new(args.input.as_bytes())
+    } else if args.input == STDIN_PATH {
+        Box::new(io::stdin().lock())
+    } else {
+        let file = fs::File::open(&args.input).expect("Could read not input from file.");
+        Box::new(BufReader::new(file))
+    }
+}
+
+fn expect_streamed<T>(result: Result<T, StreamError>) -> T {
+    result.unwrap_or_else(|error| panic!("Error in parsing input: {}", error))
+}
+
 fn abort_for_invalid_day(unknown_day: u32) {
     eprint!("No solution for day with number: {unknown_day}");
     std::process::exit(1);
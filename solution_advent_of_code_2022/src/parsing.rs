@@ -1,7 +1,44 @@
-use core::fmt::Debug;
+pub mod combinators;
+
+use core::fmt::{Debug, Display};
 use regex::Regex;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io::{self, BufRead};
 use std::str::FromStr;
 
+thread_local! {
+    static COMPILED_PATTERNS: RefCell<HashMap<String, Regex>> = RefCell::new(HashMap::new());
+}
+
+/// Error of the fallible parsing functions. Line numbers start at 1 and are
+/// added by whoever knows in which line the problem is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Error while solving from a reader instead of a whole string.
+#[derive(Debug)]
+pub enum StreamError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+/// Regex compiled once for extracting the same kind of line over and over.
+#[derive(Debug, Clone)]
+pub struct CaptureParser {
+    regex: Regex,
+}
+
+/// Typed values out of the groups of a regex, implemented for tuples of up to
+/// four values which implement `FromStr`.
+pub trait FromCaptures: Sized {
+    const NUMBER_OF_GROUPS: usize;
+    fn from_captures(captures: &[&str]) -> Result<Self, ParseError>;
+}
+
 #[derive(Debug)]
 pub enum ExtraxtSeqRegexError {
     RegexPatternError(regex::Error),
@@ -15,7 +52,7 @@ pub fn get_seq_from_regex<'a>(
 ) -> Result<Vec<&'a str>, ExtraxtSeqRegexError> {
     let mut output: Vec<&'a str> = Vec::with_capacity(number_of_groups);
 
-    let re = Regex::new(pattern).map_err(|error| ExtraxtSeqRegexError::RegexPatternError(error))?;
+    let re = get_compiled(pattern).map_err(ExtraxtSeqRegexError::RegexPatternError)?;
     let captures = re
         .captures(input.trim())
         .ok_or(ExtraxtSeqRegexError::NoMatch)?;
@@ -30,24 +67,213 @@ pub fn get_seq_from_regex<'a>(
     Ok(output)
 }
 
+fn get_compiled(pattern: &str) -> Result<Regex, regex::Error> {
+    COMPILED_PATTERNS.with(|cache| {
+        if let Some(compiled) = cache.borrow().get(pattern) {
+            return Ok(compiled.clone());
+        }
+
+        let compiled = Regex::new(pattern)?;
+        cache
+            .borrow_mut()
+            .insert(pattern.to_string(), compiled.clone());
+        Ok(compiled)
+    })
+}
+
 pub fn get_parsed_sep_by<T>(line: &str, sep: &str) -> Vec<T>
 where
     T: std::str::FromStr,
     <T as FromStr>::Err: Debug,
 {
-    line.split(sep)
-        .map(|to_parse| to_parse.parse().expect("Could not parse to desired type"))
-        .collect()
+    try_parsed_sep_by(line, sep).unwrap_or_else(|error| panic!("{}", error))
 }
 
 pub fn strip_away_left_part<'a>(strip_away_from: &'a str, prefix: &str) -> &'a str {
-    strip_away_from
-        .trim()
-        .strip_prefix(prefix)
-        .expect("Could not strip away")
+    try_strip_away_left_part(strip_away_from, prefix).unwrap_or_else(|error| panic!("{}", error))
 }
 
+pub fn try_parse<T>(to_parse: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    to_parse.parse().map_err(|error| {
+        ParseError::new(format!(
+            "Could not parse \"{}\" into {}: {:?}",
+            to_parse,
+            std::any::type_name::<T>(),
+            error
+        ))
+    })
+}
+
+pub fn try_parsed_sep_by<T>(line: &str, sep: &str) -> Result<Vec<T>, ParseError>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    line.split(sep).map(try_parse).collect()
+}
+
+pub fn try_strip_away_left_part<'a>(
+    strip_away_from: &'a str,
+    prefix: &str,
+) -> Result<&'a str, ParseError> {
+    strip_away_from.trim().strip_prefix(prefix).ok_or_else(|| {
+        ParseError::new(format!(
+            "Expected \"{}\" at the start of \"{}\"",
+            prefix,
+            strip_away_from.trim()
+        ))
+    })
+}
+
+/// Parses every line of the input, errors get the number of their line.
+pub fn parse_each_line<T, F>(input: &str, parse_line: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    input
+        .lines()
+        .enumerate()
+        .map(|(index, line)| parse_line(line).map_err(|error| error.at_line(index + 1)))
+        .collect()
+}
+
+/// Calls `on_line` for every line of the reader with only one line in memory
+/// at a time. Line endings are removed, parse errors get the number of their
+/// line.
+pub fn for_each_line<R, F>(mut reader: R, mut on_line: F) -> Result<(), StreamError>
+where
+    R: BufRead,
+    F: FnMut(&str) -> Result<(), ParseError>,
+{
+    let mut line = String::new();
+    let mut line_number = 0;
+
+    while reader.read_line(&mut line)? != 0 {
+        line_number += 1;
+        let without_ending = line.trim_end_matches(['\n', '\r']);
+        on_line(without_ending).map_err(|error| error.at_line(line_number))?;
+        line.clear();
+    }
+
+    Ok(())
+}
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            line: None,
+            message: message.into(),
+        }
+    }
+
+    /// Keeps the line of an error which already knows its line.
+    pub fn at_line(self, line: usize) -> Self {
+        Self {
+            line: self.line.or(Some(line)),
+            ..self
+        }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "Line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Display for StreamError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StreamError::Io(error) => write!(f, "Could not read input: {}", error),
+            StreamError::Parse(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl From<io::Error> for StreamError {
+    fn from(error: io::Error) -> Self {
+        StreamError::Io(error)
+    }
+}
+
+impl From<ParseError> for StreamError {
+    fn from(error: ParseError) -> Self {
+        StreamError::Parse(error)
+    }
+}
+
+impl CaptureParser {
+    pub fn new(pattern: &str) -> Result<Self, ParseError> {
+        Regex::new(pattern)
+            .map(|regex| Self { regex })
+            .map_err(|error| ParseError::new(format!("Invalid pattern {}: {}", pattern, error)))
+    }
+
+    /// Texts of the first `number_of_groups` groups.
+    pub fn captures<'a>(
+        &self,
+        input: &'a str,
+        number_of_groups: usize,
+    ) -> Result<Vec<&'a str>, ParseError> {
+        let captures = self.regex.captures(input.trim()).ok_or_else(|| {
+            ParseError::new(format!(
+                "\"{}\" does not match {}",
+                input.trim(),
+                self.regex.as_str()
+            ))
+        })?;
+
+        (1..=number_of_groups)
+            .map(|index| {
+                captures
+                    .get(index)
+                    .map(|group| group.as_str())
+                    .ok_or_else(|| ParseError::new(format!("No group {} in \"{}\"", index, input)))
+            })
+            .collect()
+    }
+
+    pub fn parse<T: FromCaptures>(&self, input: &str) -> Result<T, ParseError> {
+        T::from_captures(&self.captures(input, T::NUMBER_OF_GROUPS)?)
+    }
+}
+
+macro_rules! impl_from_captures {
+    ($number:literal; $($element:ident $index:tt),*) => {
+        impl<$($element),*> FromCaptures for ($($element,)*)
+        where
+            $($element: FromStr, <$element as FromStr>::Err: Debug,)*
+        {
+            const NUMBER_OF_GROUPS: usize = $number;
+
+            fn from_captures(captures: &[&str]) -> Result<Self, ParseError> {
+                Ok(($(try_parse::<$element>(captures[$index])?,)*))
+            }
+        }
+    };
+}
+
+impl_from_captures!(1; A 0);
+impl_from_captures!(2; A 0, B 1);
+impl_from_captures!(3; A 0, B 1, C 2);
+impl_from_captures!(4; A 0, B 1, C 2, D 3);
+
 pub type Lines<'a> = Vec<&'a str>;
+
+/// Lines between blank lines and the number of its first line, counted from 1.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Block<'a> {
+    pub first_line: usize,
+    pub lines: Lines<'a>,
+}
+
 pub fn split_lines_where_after<'a, P>(input: &'a str, perdicate: P) -> (Lines<'a>, Lines<'a>)
 where
     P: Fn(&str) -> bool,
@@ -62,24 +288,39 @@ where
     split_lines_where(input, perdicate, false)
 }
 
-pub fn split_chunks_where<'a, P>(input: &'a str, perdicate: P) -> Vec<Vec<&'a str>>
-where
-    P: Fn(&str) -> bool,
-{
-    let mut chunks = Vec::new();
-    chunks.push(Vec::new());
-    let mut counter = 0;
-
-    for line in input.lines() {
-        if perdicate(line) {
-            chunks.push(Vec::new());
-            counter += 1;
-        } else {
-            chunks[counter].push(line);
+/// Splits the input at lines which are empty or only contain whitespace.
+/// Windows line endings are removed. Several blank lines in a row and blank
+/// lines at the start or end never produce empty blocks.
+pub fn split_blocks(input: &str) -> Vec<Block<'_>> {
+    let mut blocks = Vec::new();
+    let mut current = Block {
+        first_line: 1,
+        lines: Vec::new(),
+    };
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if !line.trim().is_empty() {
+            if current.lines.is_empty() {
+                current.first_line = index + 1;
+            }
+            current.lines.push(line);
+        } else if !current.lines.is_empty() {
+            blocks.push(std::mem::replace(
+                &mut current,
+                Block {
+                    first_line: index + 1,
+                    lines: Vec::new(),
+                },
+            ));
         }
     }
 
-    chunks
+    if !current.lines.is_empty() {
+        blocks.push(current);
+    }
+
+    blocks
 }
 
 fn split_lines_where<'a, P>(input: &'a str, perdicate: P, after: bool) -> (Lines<'a>, Lines<'a>)
@@ -105,3 +346,59 @@ pub fn map_line_to_chunk_vec(line: &str, chunk_size: usize) -> Vec<Vec<char>> {
         .map(|slice| slice.to_vec())
         .collect()
 }
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_capture_parser_into_tuple() {
+        // Set up
+        let parser = CaptureParser::new(r"move (\d+) from (\d+) to (\w+)").unwrap();
+
+        // Act
+        let parsed: Result<(u32, u8, String), _> = parser.parse("move 12 from 3 to x");
+        let wrong_type: Result<(u32, u8, u8), _> = parser.parse("move 12 from 3 to x");
+
+        // Assert
+        assert_eq!(Ok((12, 3, "x".to_string())), parsed);
+        assert!(wrong_type.is_err());
+    }
+
+    #[test]
+    fn test_split_blocks() {
+        // Set up
+        let input = "\r\n1\r\n2\r\n \t\r\n\r\n3\r\n\r\n";
+
+        // Act
+        let actual = split_blocks(input);
+
+        // Assert
+        assert_eq!(
+            vec![
+                Block {
+                    first_line: 2,
+                    lines: vec!["1", "2"]
+                },
+                Block {
+                    first_line: 6,
+                    lines: vec!["3"]
+                }
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn test_parse_each_line_reports_line() {
+        // Set up
+        let input = "1, 2\n3, x\n";
+
+        // Act
+        let actual = parse_each_line(input, |line| try_parsed_sep_by::<u32>(line, ", "));
+
+        // Assert
+        assert_eq!(Some(2), actual.unwrap_err().line);
+    }
+}
@@ -0,0 +1,277 @@
+use super::ParseError;
+use core::fmt::Debug;
+use std::str::FromStr;
+
+/// Parsed value and the input which is left after it.
+pub type ParseResult<'a, T> = Result<(T, &'a str), ParseError>;
+
+/// Something which takes a value from the start of the input. Implemented for
+/// every function or closure with the right signature.
+pub trait Parser<T> {
+    fn parse_next<'a>(&self, input: &'a str) -> ParseResult<'a, T>;
+}
+
+impl<T, F> Parser<T> for F
+where
+    F: Fn(&str) -> ParseResult<'_, T>,
+{
+    fn parse_next<'a>(&self, input: &'a str) -> ParseResult<'a, T> {
+        self(input)
+    }
+}
+
+/// Parsers one after another, implemented for tuples of up to five parsers.
+pub trait Sequence<T> {
+    fn parse_sequence<'a>(&self, input: &'a str) -> ParseResult<'a, T>;
+}
+
+/// Parsers tried in order until one succeeds, implemented for tuples of up to
+/// five parsers of the same value.
+pub trait Alternatives<T> {
+    fn parse_first<'a>(&self, input: &'a str) -> ParseResult<'a, T>;
+}
+
+/// Only there to tie the lifetime of the returned rest to the input for
+/// closures, which the compiler does not infer on its own.
+fn parser<T, F>(to_parse: F) -> F
+where
+    F: Fn(&str) -> ParseResult<'_, T>,
+{
+    to_parse
+}
+
+pub fn literal(expected: &'static str) -> impl Parser<&'static str> {
+    parser(move |input: &str| match input.strip_prefix(expected) {
+        Some(rest) => Ok((expected, rest)),
+        None => Err(ParseError::new(format!(
+            "Expected \"{}\" but found \"{}\"",
+            expected, input
+        ))),
+    })
+}
+
+/// Digits with an optional minus in front, parsed into any number type.
+pub fn integer<T>() -> impl Parser<T>
+where
+    T: FromStr,
+    <T as FromStr>::Err: Debug,
+{
+    parser(|input: &str| {
+        let sign_length = usize::from(input.starts_with('-'));
+        let digits_length = input[sign_length..]
+            .find(|next: char| !next.is_ascii_digit())
+            .unwrap_or(input.len() - sign_length);
+
+        if digits_length == 0 {
+            return Err(ParseError::new(format!(
+                "Expected a number but found \"{}\"",
+                input
+            )));
+        }
+
+        let (number, rest) = input.split_at(sign_length + digits_length);
+        Ok((super::try_parse(number)?, rest))
+    })
+}
+
+/// Everything up to the next whitespace, at least one character.
+pub fn token() -> impl Parser<String> {
+    parser(|input: &str| {
+        let length = input.find(char::is_whitespace).unwrap_or(input.len());
+        if length == 0 {
+            return Err(ParseError::new(format!(
+                "Expected a word but found \"{}\"",
+                input
+            )));
+        }
+
+        let (word, rest) = input.split_at(length);
+        Ok((word.to_string(), rest))
+    })
+}
+
+/// One or more spaces or tabs.
+pub fn spaces() -> impl Parser<()> {
+    parser(|input: &str| {
+        let rest = input.trim_start_matches([' ', '\t']);
+        if rest.len() == input.len() {
+            Err(ParseError::new(format!(
+                "Expected a space but found \"{}\"",
+                input
+            )))
+        } else {
+            Ok(((), rest))
+        }
+    })
+}
+
+/// Skips any whitespace before and after the value.
+pub fn trimmed<T>(inner: impl Parser<T>) -> impl Parser<T> {
+    parser(move |input: &str| {
+        let (value, rest) = inner.parse_next(input.trim_start())?;
+        Ok((value, rest.trim_start()))
+    })
+}
+
+pub fn map<T, U>(inner: impl Parser<T>, to_map: impl Fn(T) -> U) -> impl Parser<U> {
+    parser(move |input: &str| {
+        let (value, rest) = inner.parse_next(input)?;
+        Ok((to_map(value), rest))
+    })
+}
+
+/// Like `map` but the conversion may fail, for example on a letter which is
+/// not known.
+pub fn try_map<T, U>(
+    inner: impl Parser<T>,
+    to_map: impl Fn(T) -> Result<U, ParseError>,
+) -> impl Parser<U> {
+    parser(move |input: &str| {
+        let (value, rest) = inner.parse_next(input)?;
+        Ok((to_map(value)?, rest))
+    })
+}
+
+/// Gives a fixed value if the inner parser succeeds, like a letter for an enum
+/// variant.
+pub fn value<T: Clone, U>(inner: impl Parser<U>, value: T) -> impl Parser<T> {
+    map(inner, move |_| value.clone())
+}
+
+/// At least one item with a separator between each of them. A separator
+/// without an item after it is left in the rest, like trailing spaces.
+pub fn separated<T, S>(item: impl Parser<T>, separator: impl Parser<S>) -> impl Parser<Vec<T>> {
+    parser(move |input: &str| {
+        let (first, mut rest) = item.parse_next(input)?;
+        let mut items = vec![first];
+
+        while let Ok((_, after_separator)) = separator.parse_next(rest) {
+            match item.parse_next(after_separator) {
+                Ok((next, after_item)) => {
+                    items.push(next);
+                    rest = after_item;
+                }
+                Err(_) => break,
+            }
+        }
+
+        Ok((items, rest))
+    })
+}
+
+pub fn tuple<T>(parsers: impl Sequence<T>) -> impl Parser<T> {
+    parser(move |input: &str| parsers.parse_sequence(input))
+}
+
+pub fn alt<T>(parsers: impl Alternatives<T>) -> impl Parser<T> {
+    parser(move |input: &str| parsers.parse_first(input))
+}
+
+/// Parses the whole input, anything left over is an error.
+pub fn parse_whole<T>(whole: &impl Parser<T>, input: &str) -> Result<T, ParseError> {
+    match whole.parse_next(input)? {
+        (value, "") => Ok(value),
+        (_, rest) => Err(ParseError::new(format!(
+            "Unexpected \"{}\" at the end of \"{}\"",
+            rest, input
+        ))),
+    }
+}
+
+/// Every line has to match as a whole. Errors get the number of their line.
+pub fn parse_lines<T>(line: &impl Parser<T>, input: &str) -> Result<Vec<T>, ParseError> {
+    super::parse_each_line(input, |next_line| parse_whole(line, next_line))
+}
+
+/// Parses blocks of lines separated by empty lines. Line numbers of errors in
+/// a block are counted from the start of the whole input.
+pub fn parse_blocks<T, F>(input: &str, block: F) -> Result<Vec<T>, ParseError>
+where
+    F: Fn(&str) -> Result<T, ParseError>,
+{
+    super::split_blocks(input)
+        .into_iter()
+        .map(|next| {
+            block(&next.lines.join("\n")).map_err(|error| ParseError {
+                line: Some(next.first_line + error.line.unwrap_or(1) - 1),
+                ..error
+            })
+        })
+        .collect()
+}
+
+macro_rules! impl_sequence_and_alternatives {
+    ($($parser:ident $value:ident),*) => {
+        impl<$($value, $parser: Parser<$value>),*> Sequence<($($value,)*)> for ($($parser,)*) {
+            #[allow(non_snake_case)]
+            fn parse_sequence<'a>(&self, input: &'a str) -> ParseResult<'a, ($($value,)*)> {
+                let ($($parser,)*) = self;
+                let rest = input;
+                $(let ($value, rest) = $parser.parse_next(rest)?;)*
+                Ok((($($value,)*), rest))
+            }
+        }
+
+        impl<T, $($parser: Parser<T>),*> Alternatives<T> for ($($parser,)*) {
+            #[allow(non_snake_case)]
+            fn parse_first<'a>(&self, input: &'a str) -> ParseResult<'a, T> {
+                let ($($parser,)*) = self;
+                let mut messages = Vec::new();
+                $(
+                    match $parser.parse_next(input) {
+                        Ok(parsed) => return Ok(parsed),
+                        Err(error) => messages.push(error.message),
+                    }
+                )*
+                Err(ParseError::new(messages.join(" or ")))
+            }
+        }
+    };
+}
+
+impl_sequence_and_alternatives!(P1 V1, P2 V2);
+impl_sequence_and_alternatives!(P1 V1, P2 V2, P3 V3);
+impl_sequence_and_alternatives!(P1 V1, P2 V2, P3 V3, P4 V4);
+impl_sequence_and_alternatives!(P1 V1, P2 V2, P3 V3, P4 V4, P5 V5);
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+
+    #[test]
+    fn test_combined_parser() {
+        // Set up
+        let range = map(
+            tuple((integer::<i32>(), literal("-"), integer::<i32>())),
+            |(start, _, end)| (start, end),
+        );
+        let pairs = separated(range, literal(","));
+
+        // Act
+        let actual = parse_lines(&pairs, "2-4,-6-8\n5-7\n");
+
+        // Assert
+        assert_eq!(Ok(vec![vec![(2, 4), (-6, 8)], vec![(5, 7)]]), actual);
+    }
+
+    #[test]
+    fn test_errors_of_lines_and_blocks() {
+        // Set up
+        let direction = alt((value(literal("U"), 'U'), value(literal("D"), 'D')));
+        let input = "U\nD\n\nD\nX\n";
+
+        // Act
+        let actual = parse_blocks(input, |block| parse_lines(&direction, block));
+
+        // Assert
+        assert_eq!(
+            Err(ParseError {
+                line: Some(5),
+                message: "Expected \"U\" but found \"X\" or Expected \"D\" but found \"X\""
+                    .to_string()
+            }),
+            actual
+        );
+    }
+}
@@ -1,5 +1,3 @@
-use std::iter::repeat;
-
 pub fn create_grid_with_value<T>(height: usize, width: usize, cell_value: &T) -> Vec<Vec<T>>
 where
     T: Clone,
@@ -7,7 +5,7 @@ where
     let mut output: Vec<Vec<T>> = Default::default();
 
     for _ in 0..height {
-        let row = repeat(cell_value.clone()).take(width).collect::<Vec<T>>();
+        let row = std::iter::repeat_n(cell_value.clone(), width).collect::<Vec<T>>();
         output.push(row);
     }
 